@@ -1,4 +1,4 @@
-use logprob::{LogProb, LogSumExp};
+use logprob::{log_sum_exp, log_sum_exp_f64, LogProb, LogSumExp};
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
 
@@ -47,3 +47,52 @@ fn allocate_log_exp_clamped(n: u32) -> LogProb<f64> {
 fn dont_allocate_log_exp_clamped(n: u32) -> LogProb<f64> {
     divan::black_box(get_big_vector_overflow(n).into_iter()).log_sum_exp_clamped_no_alloc()
 }
+
+fn get_big_vector_sorted(n: u32) -> Vec<LogProb<f64>> {
+    let mut v = get_big_vector(n);
+    v.sort_by(|a, b| b.cmp(a));
+    v
+}
+
+#[divan::bench(args = SIZES)]
+fn dont_allocate_log_exp_clamped_desc_input(bencher: divan::Bencher, n: u32) {
+    bencher
+        .with_inputs(|| get_big_vector_sorted(n))
+        .bench_values(|v| v.into_iter().log_sum_exp_clamped_no_alloc())
+}
+
+#[divan::bench(args = SIZES)]
+fn sorted_desc_log_exp_clamped(bencher: divan::Bencher, n: u32) {
+    bencher
+        .with_inputs(|| get_big_vector_sorted(n))
+        .bench_values(|v| v.into_iter().log_sum_exp_clamped_sorted_desc())
+}
+
+#[divan::bench]
+fn generic_log_sum_exp_slice(bencher: divan::Bencher) {
+    bencher
+        .with_inputs(|| get_big_vector(10_000))
+        .bench_refs(|v| log_sum_exp(v).unwrap())
+}
+
+#[divan::bench]
+fn concrete_log_sum_exp_f64(bencher: divan::Bencher) {
+    bencher
+        .with_inputs(|| get_big_vector(10_000))
+        .bench_refs(|v| log_sum_exp_f64(v).unwrap())
+}
+
+// Borrowed iterators are used so that neither version can reuse the input's allocation.
+#[divan::bench]
+fn growing_vec_log_sum_exp(bencher: divan::Bencher) {
+    bencher
+        .with_inputs(|| get_big_vector(10_000))
+        .bench_refs(|v| v.iter().log_sum_exp().unwrap())
+}
+
+#[divan::bench]
+fn exact_vec_log_sum_exp(bencher: divan::Bencher) {
+    bencher
+        .with_inputs(|| get_big_vector(10_000))
+        .bench_refs(|v| v.iter().log_sum_exp_exact().unwrap())
+}
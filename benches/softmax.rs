@@ -0,0 +1,48 @@
+use logprob::{log_sum_exp_clamped, log_sum_exp_clamped_f32, softmax, softmax_f32_fast, LogProb};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+fn main() {
+    // Run registered benchmarks.
+    divan::main();
+}
+
+fn get_logits(n: u32) -> Vec<f32> {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    (0..n).map(|_| rng.gen::<f32>() * 20.0 - 10.0).collect()
+}
+
+const SIZES: &[u32] = &[10, 100, 1000, 4000, 10_000];
+
+#[divan::bench(args = SIZES)]
+fn generic_softmax(bencher: divan::Bencher<'_, '_>, n: u32) {
+    let logits = get_logits(n);
+    bencher.bench_local(|| -> Vec<LogProb<f32>> {
+        softmax(divan::black_box(&logits)).unwrap().collect()
+    });
+}
+
+#[divan::bench(args = SIZES)]
+fn fast_softmax(bencher: divan::Bencher<'_, '_>, n: u32) {
+    let logits = get_logits(n);
+    bencher.bench_local(|| softmax_f32_fast(divan::black_box(&logits)).unwrap());
+}
+
+fn get_log_probs(n: u32) -> Vec<LogProb<f32>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    (0..n)
+        .map(|_| LogProb::from_raw_prob(rng.gen::<f32>()).unwrap())
+        .collect()
+}
+
+#[divan::bench(args = SIZES)]
+fn scalar_log_sum_exp_clamped(bencher: divan::Bencher<'_, '_>, n: u32) {
+    let vals = get_log_probs(n);
+    bencher.bench_local(|| log_sum_exp_clamped(divan::black_box(&vals)));
+}
+
+#[divan::bench(args = SIZES)]
+fn fast_log_sum_exp_clamped(bencher: divan::Bencher<'_, '_>, n: u32) {
+    let vals = get_log_probs(n);
+    bencher.bench_local(|| log_sum_exp_clamped_f32(divan::black_box(&vals)));
+}
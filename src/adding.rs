@@ -1,22 +1,42 @@
 use std::borrow::Borrow;
 
-use super::{Float, LogProb, ProbabilitiesSumToGreaterThanOne};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{
+    as_float_slice, Float, InvalidInput, LogProb, LogSumExpError, ProbabilitiesSumToGreaterThanOne,
+};
 
 pub trait Ln2: Sized {
     const LN_2: Self;
     const ZERO: Self;
     const NEG_INFINITY: Self;
+
+    ///The logsumexp of plain floats with the vectorized kernels of the `simd` feature where there
+    ///are some for this type, and with [`log_sum_exp_float_slice`] otherwise.
+    fn log_sum_exp_simd(val: &[Self]) -> Self;
 }
 
 impl Ln2 for f32 {
     const LN_2: Self = std::f32::consts::LN_2;
     const ZERO: Self = 0.0;
-    const NEG_INFINITY: Self = std::f32::NEG_INFINITY;
+    const NEG_INFINITY: Self = f32::NEG_INFINITY;
+
+    fn log_sum_exp_simd(val: &[Self]) -> Self {
+        #[cfg(feature = "simd")]
+        return super::simd::log_sum_exp_chunked(val);
+        #[cfg(not(feature = "simd"))]
+        return log_sum_exp_float_slice(val);
+    }
 }
 impl Ln2 for f64 {
     const LN_2: Self = std::f64::consts::LN_2;
     const ZERO: Self = 0.0;
-    const NEG_INFINITY: Self = std::f64::NEG_INFINITY;
+    const NEG_INFINITY: Self = f64::NEG_INFINITY;
+
+    fn log_sum_exp_simd(val: &[Self]) -> Self {
+        log_sum_exp_float_slice(val)
+    }
 }
 
 impl<T: Float + Ln2> LogProb<T> {
@@ -39,7 +59,14 @@ impl<T: Float + Ln2> LogProb<T> {
         Ok(LogProb::new(Self::add_log_prob_internal(self.0, y))?)
     }
 
-    /// Adds log probabilities but clamping at 0.0.
+    /// Adds log probabilities but clamping at 0.0. Only a sum which overflows 0.0 is clamped: if
+    /// either operand is impossible, the result is exactly the other operand.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let half = LogProb::from_raw_prob(0.5_f64).unwrap();
+    /// assert_eq!(LogProb::impossible().add_log_prob_clamped(half), half);
+    /// assert_eq!(half.add_log_prob_clamped(LogProb::new(-0.1).unwrap()), LogProb::certain());
+    /// ```
     #[inline(always)]
     pub fn add_log_prob_clamped(&self, y: LogProb<T>) -> LogProb<T> {
         match self.add_log_prob(y) {
@@ -75,10 +102,54 @@ fn log_sum_exp_allocate_inner<
     log_sum_exp_inner(&v, max)
 }
 
+///Stable logsumexp over plain floats which returns the max directly if it is infinite (so an
+///all `-inf` slice gives `-inf` rather than `NaN`).
+pub(crate) fn log_sum_exp_float_slice<T: Float + Ln2>(val: &[T]) -> T {
+    let max = val.iter().fold(T::NEG_INFINITY, |acc, &x| acc.max(x));
+    if max.is_infinite() {
+        return max;
+    }
+    val.iter()
+        .fold(T::ZERO, |acc, &x| acc + (x - max).exp())
+        .ln()
+        + max
+}
+
+///Wraps a float as a [`LogProb`], clamping anything greater than 0.0 to 0.0.
+#[inline]
+pub(crate) fn clamp_log_prob<T: Float + Ln2>(val: T) -> LogProb<T> {
+    match LogProb::new(val) {
+        Ok(x) => x,
+        Err(_) => LogProb(T::ZERO),
+    }
+}
+
+///Normalizes `vals` in place so that they sum to one (as raw probabilities) and returns the
+///logsumexp they were divided by. If every element is impossible, they are left as they are.
+pub(crate) fn normalize_in_place<T: Float + Ln2>(vals: &mut [LogProb<T>]) -> T {
+    let max = vals.iter().fold(T::NEG_INFINITY, |acc, x| acc.max(x.0));
+    if max == T::NEG_INFINITY {
+        return max;
+    }
+    let total = vals
+        .iter()
+        .fold(T::ZERO, |acc, x| acc + (x.0 - max).exp())
+        .ln()
+        + max;
+    vals.iter_mut()
+        .for_each(|x| *x = clamp_log_prob(x.0 - total));
+    total
+}
+
+///Computes the logsumexp of `val` given its maximum. If the maximum is impossible, so is every
+///element, and it is returned directly as `(x - max)` would otherwise be `-inf - -inf = NaN`.
 fn log_sum_exp_inner<T: Float + std::iter::Sum + Ln2, L: Borrow<LogProb<T>>>(
     val: &[L],
     max: LogProb<T>,
 ) -> T {
+    if max.0 == T::NEG_INFINITY {
+        return max.0;
+    }
     val.iter()
         .map(|x| (x.borrow().0 - max.0).exp())
         .sum::<T>()
@@ -112,6 +183,33 @@ pub fn log_sum_exp_clamped<T: Float + std::iter::Sum + Ln2, L: Borrow<LogProb<T>
     }
 }
 
+///Adds up a slice of [`LogProb`] (as raw probabilities) like [`log_sum_exp`], but first checks
+///that no element is NaN, which can only happen if it was made with [`LogProb::new_unchecked`].
+///Returns [`LogSumExpError::InvalidInput`] if one is, and
+///[`LogSumExpError::ProbabilitiesSumToGreaterThanOne`] if the sum is not a valid [`LogProb`].
+/// ```
+/// # use logprob::{LogProb, LogSumExpError, log_sum_exp_checked};
+/// // SAFETY: deliberately invalid, to check that log_sum_exp_checked rejects it.
+/// let x = [LogProb::new(-1.0_f64).unwrap(), unsafe { LogProb::new_unchecked(f64::NAN) }];
+/// assert_eq!(log_sum_exp_checked(&x), Err(LogSumExpError::InvalidInput));
+/// let x = [LogProb::new(-0.5_f64).unwrap(); 3];
+/// assert_eq!(
+///     log_sum_exp_checked(&x),
+///     Err(LogSumExpError::ProbabilitiesSumToGreaterThanOne)
+/// );
+/// ```
+pub fn log_sum_exp_checked<T: Float + Ln2>(
+    val: &[LogProb<T>],
+) -> Result<LogProb<T>, LogSumExpError> {
+    let val = as_float_slice(val);
+    if val.iter().any(|x| x.is_nan()) {
+        return Err(InvalidInput.into());
+    }
+    let total =
+        LogProb::new(log_sum_exp_float_slice(val)).map_err(|_| ProbabilitiesSumToGreaterThanOne)?;
+    Ok(total)
+}
+
 ///Adds up a slice of [`LogProb`] (as raw probabilities) and returns a float with their sum,
 ///regardless of if it would be a valid [`LogProb`].
 pub fn log_sum_exp_float<T: Float + std::iter::Sum + Ln2, L: Borrow<LogProb<T>> + Ord>(
@@ -123,6 +221,360 @@ pub fn log_sum_exp_float<T: Float + std::iter::Sum + Ln2, L: Borrow<LogProb<T>>
     }
 }
 
+///Adds up a slice of plain floats holding log-probabilities (as raw probabilities) and returns a
+///float with their sum. There is no validation, so this is useful for trusted floats from an
+///external source which will be consumed as floats anyway.
+/// ```
+/// # use logprob::log_sum_exp_raw;
+/// let x = log_sum_exp_raw(&[0.25_f64.ln(), 0.5_f64.ln()]);
+/// approx::assert_relative_eq!(x, 0.75_f64.ln());
+/// ```
+pub fn log_sum_exp_raw<T: Float + Ln2 + std::iter::Sum>(vals: &[T]) -> T {
+    log_sum_exp_float_slice(vals)
+}
+
+///Adds up a slice of `LogProb<f64>` (as raw probabilities) like [`log_sum_exp`]. The slice is
+///reinterpreted as plain floats, so the reduction is a simple loop over a contiguous slice which
+///the compiler can optimise better than the generic version.
+/// ```
+/// # use logprob::{LogProb, log_sum_exp_f64};
+/// let x = LogProb::from_raw_prob(0.25_f64).unwrap();
+/// assert_eq!(log_sum_exp_f64(&[x, x]).unwrap(), LogProb::from_raw_prob(0.5).unwrap());
+/// ```
+pub fn log_sum_exp_f64(
+    vals: &[LogProb<f64>],
+) -> Result<LogProb<f64>, ProbabilitiesSumToGreaterThanOne> {
+    Ok(LogProb::new(log_sum_exp_float_slice(as_float_slice(vals)))?)
+}
+
+///Adds up a slice of `LogProb<f32>` (as raw probabilities) like [`log_sum_exp`]. See
+///[`log_sum_exp_f64`].
+pub fn log_sum_exp_f32(
+    vals: &[LogProb<f32>],
+) -> Result<LogProb<f32>, ProbabilitiesSumToGreaterThanOne> {
+    Ok(LogProb::new(log_sum_exp_float_slice(as_float_slice(vals)))?)
+}
+
+///Adds up each group of a ragged batch of [`LogProb`] (as raw probabilities), returning one
+///result per group like [`log_sum_exp`]. Each group is reinterpreted as a slice of floats, so
+///nothing is allocated apart from the output.
+/// ```
+/// # use logprob::{LogProb, log_sum_exp_groups};
+/// let x = LogProb::from_raw_prob(0.25_f64).unwrap();
+/// let sums = log_sum_exp_groups(&[vec![x], vec![x, x], vec![]]);
+/// assert_eq!(sums[1], Ok(LogProb::from_raw_prob(0.5).unwrap()));
+/// assert_eq!(sums[2], Ok(LogProb::impossible()));
+/// ```
+pub fn log_sum_exp_groups<T: Float + Ln2 + std::iter::Sum>(
+    groups: &[Vec<LogProb<T>>],
+) -> Vec<Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>> {
+    groups
+        .iter()
+        .map(|group| {
+            Ok(LogProb::new(log_sum_exp_float_slice(as_float_slice(
+                group,
+            )))?)
+        })
+        .collect()
+}
+
+///Adds up the [`LogProb`] returned by `f` for every index in `0..n` (as raw probabilities) like
+///[`log_sum_exp`], without collecting them into a slice. It makes two passes over the indices,
+///one to find the maximum and one to add up the rescaled terms, so `f` is called twice for each
+///index and should return the same value both times.
+/// ```
+/// # use logprob::{LogProb, log_sum_exp_fn};
+/// let x = log_sum_exp_fn(4, |_| LogProb::from_raw_prob(0.25_f64).unwrap()).unwrap();
+/// assert_eq!(x, LogProb::certain());
+/// ```
+pub fn log_sum_exp_fn<T: Float + Ln2, F: FnMut(usize) -> LogProb<T>>(
+    n: usize,
+    mut f: F,
+) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne> {
+    let max = (0..n).fold(T::NEG_INFINITY, |acc, i| acc.max(f(i).0));
+    if max.is_infinite() {
+        return Ok(LogProb::new(max)?);
+    }
+    let total = (0..n).fold(T::ZERO, |acc, i| acc + (f(i).0 - max).exp());
+    Ok(LogProb::new(total.ln() + max)?)
+}
+
+///Adds two plain floats holding log-probabilities (as raw probabilities), matching numpy's
+///`logaddexp`. There is no validation, so the result may be greater than 0.0.
+/// ```
+/// # use logprob::logaddexp;
+/// approx::assert_relative_eq!(logaddexp(1e-50_f64.ln(), 2.5e-50_f64.ln()), 3.5e-50_f64.ln());
+/// ```
+#[inline]
+pub fn logaddexp<T: Float + Ln2>(a: T, b: T) -> T {
+    LogProb::<T>::add_log_prob_internal(a, b)
+}
+
+///Calculates `ln(1 - exp(x))` for `x <= 0.0`, switching between `expm1` and `ln_1p` to keep
+///precision on either side of `-ln(2)`.
+#[inline]
+fn log1mexp<T: Float + Ln2>(x: T) -> T {
+    if x > -T::LN_2 {
+        (-x.exp_m1()).ln()
+    } else {
+        (-x.exp()).ln_1p()
+    }
+}
+
+///Subtracts two plain floats holding log-probabilities (as raw probabilities), calculating
+///`ln(exp(a) - exp(b))` as `a + ln(1 - exp(b - a))`. This is the counterpart to [`logaddexp`]
+///and matches scipy's `logsumexp` with weights of 1 and -1. Since the result would be the
+///logarithm of a negative number, NaN is returned when `b > a`. If `a == b`, the result is
+///negative infinity (unless both are positive infinity, which is NaN).
+/// ```
+/// # use logprob::logsubexp;
+/// approx::assert_relative_eq!(logsubexp(0.75_f64.ln(), 0.25_f64.ln()), 0.5_f64.ln());
+/// assert!(logsubexp(0.25_f64.ln(), 0.75_f64.ln()).is_nan());
+/// ```
+#[inline]
+pub fn logsubexp<T: Float + Ln2>(a: T, b: T) -> T {
+    if b > a || (a == b && a == T::infinity()) {
+        T::nan()
+    } else if b == T::NEG_INFINITY {
+        a
+    } else if a == b {
+        T::NEG_INFINITY
+    } else {
+        a + log1mexp(b - a)
+    }
+}
+
+///Accumulates a logsumexp over a stream of chunks, so that the whole stream never needs to be
+///buffered. Each chunk is reduced with the usual max-rescaling and then combined with the running
+///total.
+/// ```
+/// # use logprob::{LogProb, ChunkedLogSumExp};
+/// let x = LogProb::from_raw_prob(0.25_f64).unwrap();
+/// let mut acc = ChunkedLogSumExp::new();
+/// acc.add_chunk(&[x, x]);
+/// acc.add_chunk(&[x]);
+/// assert_eq!(acc.finish(), LogProb::from_raw_prob(0.75).unwrap());
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ChunkedLogSumExp<T> {
+    total: T,
+}
+
+impl<T: Float + Ln2> ChunkedLogSumExp<T> {
+    ///Creates a new accumulator with a total of zero probability.
+    pub fn new() -> Self {
+        ChunkedLogSumExp {
+            total: T::NEG_INFINITY,
+        }
+    }
+
+    ///Adds every [`LogProb`] in `chunk` to the running total.
+    pub fn add_chunk<L: Borrow<LogProb<T>>>(&mut self, chunk: &[L]) {
+        let max = chunk
+            .iter()
+            .fold(T::NEG_INFINITY, |acc, x| acc.max(x.borrow().0));
+        if max == T::NEG_INFINITY {
+            return;
+        }
+        let chunk_total = chunk
+            .iter()
+            .fold(T::ZERO, |acc, x| acc + (x.borrow().0 - max).exp())
+            .ln()
+            + max;
+        self.total = LogProb::add_log_prob_internal(self.total, chunk_total);
+    }
+
+    ///Returns the total so far, clamped at 0.0.
+    pub fn finish(&self) -> LogProb<T> {
+        clamp_log_prob(self.total)
+    }
+}
+
+impl<T: Float + Ln2> Default for ChunkedLogSumExp<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Requires `Send + Sync` only when the `rayon` feature is enabled, so that
+///[`LogSumExpBuilder::reduce`] can hand chunks to other threads. It is implemented for every type
+///which meets that requirement, so it never needs to be implemented by hand.
+#[cfg(feature = "rayon")]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(feature = "rayon")]
+impl<T: Send + Sync> MaybeSendSync for T {}
+
+///Requires `Send + Sync` only when the `rayon` feature is enabled, so that
+///[`LogSumExpBuilder::reduce`] can hand chunks to other threads. It is implemented for every type
+///which meets that requirement, so it never needs to be implemented by hand.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSendSync {}
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSendSync for T {}
+
+///Picks how to compute the logsumexp of a slice of [`LogProb`], as a single entry point to the
+///different algorithms. By default, this is the same as [`log_sum_exp`], and each method turns on
+///one option. The options compose with each other:
+///
+///- [`Self::parallel`] splits the input into chunks whose sums are combined at the end.
+///- [`Self::pairwise`] splits each chunk in half recursively down to small blocks.
+///- [`Self::simd`] adds up each block with vectorized kernels.
+///- [`Self::kahan`] refines the sum of each block with a compensated second pass.
+/// ```
+/// # use logprob::{LogProb, LogSumExpBuilder};
+/// let x = [0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let total = LogSumExpBuilder::new().pairwise().kahan().reduce(&x).unwrap();
+/// assert_eq!(total, LogProb::from_raw_prob(0.75).unwrap());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct LogSumExpBuilder {
+    kahan: bool,
+    pairwise: bool,
+    simd: bool,
+    parallel: bool,
+}
+
+impl LogSumExpBuilder {
+    ///Creates a new builder with every option turned off, which computes the same sum as
+    ///[`log_sum_exp`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Refines the sum of each block with a compensated second pass, like
+    ///[`LogSumExp::log_sum_exp_refined`].
+    pub fn kahan(self) -> Self {
+        LogSumExpBuilder {
+            kahan: true,
+            ..self
+        }
+    }
+
+    ///Splits the input in half recursively and adds up blocks of 16 elements, so that rounding
+    ///errors grow with the log of its length.
+    pub fn pairwise(self) -> Self {
+        LogSumExpBuilder {
+            pairwise: true,
+            ..self
+        }
+    }
+
+    ///Adds up each block with the vectorized kernels of `log_sum_exp_clamped_f32`, which agree
+    ///with the usual loop to within about `1e-6`. This only applies to `f32` with the `simd`
+    ///feature; otherwise, blocks are added up with the usual loop.
+    pub fn simd(self) -> Self {
+        LogSumExpBuilder { simd: true, ..self }
+    }
+
+    ///Reduces chunks of the input in parallel with `rayon` before combining them. Without the
+    ///`rayon` feature, this still splits the input into chunks, but they are reduced one after
+    ///another on the current thread.
+    pub fn parallel(self) -> Self {
+        LogSumExpBuilder {
+            parallel: true,
+            ..self
+        }
+    }
+
+    ///Adds up `vals` (as raw probabilities) with the chosen options, where `vals` can be a slice
+    ///or any other iterator of [`LogProb`]. Like [`log_sum_exp`], this will only return `Ok` if
+    ///the sum could be a valid [`LogProb`]. The input is first collected into a vector, which
+    ///[`Self::reduce_slice`] avoids.
+    pub fn reduce<T: Float + Ln2 + MaybeSendSync>(
+        &self,
+        vals: impl IntoIterator<Item = impl Borrow<LogProb<T>>>,
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne> {
+        let vals: Vec<LogProb<T>> = vals.into_iter().map(|x| *x.borrow()).collect();
+        self.reduce_slice(&vals)
+    }
+
+    ///Adds up a slice of [`LogProb`] (as raw probabilities) like [`Self::reduce`], but without
+    ///copying it.
+    pub fn reduce_slice<T: Float + Ln2 + MaybeSendSync>(
+        &self,
+        vals: &[LogProb<T>],
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne> {
+        const CHUNK: usize = 4096;
+        let vals = as_float_slice(vals);
+        let total = if self.parallel {
+            #[cfg(feature = "rayon")]
+            let chunks = vals.par_chunks(CHUNK);
+            #[cfg(not(feature = "rayon"))]
+            let chunks = vals.chunks(CHUNK);
+            let totals: Vec<T> = chunks.map(|x| self.reduce_chunk(x)).collect();
+            log_sum_exp_float_slice(&totals)
+        } else {
+            self.reduce_chunk(vals)
+        };
+        Ok(LogProb::new(total)?)
+    }
+
+    fn reduce_chunk<T: Float + Ln2>(&self, val: &[T]) -> T {
+        const BLOCK: usize = 16;
+        if self.pairwise && val.len() > BLOCK {
+            let (left, right) = val.split_at(val.len() / 2);
+            logaddexp(self.reduce_chunk(left), self.reduce_chunk(right))
+        } else {
+            self.reduce_block(val)
+        }
+    }
+
+    fn reduce_block<T: Float + Ln2>(&self, val: &[T]) -> T {
+        let r = if self.simd {
+            T::log_sum_exp_simd(val)
+        } else {
+            log_sum_exp_float_slice(val)
+        };
+        if !self.kahan || !r.is_finite() {
+            return r;
+        }
+        // Neumaier summation of -1 + Σ exp(x_i - r).
+        let (sum, compensation) = val.iter().fold((-T::one(), T::ZERO), |(sum, c), &x| {
+            let term = (x - r).exp();
+            let t = sum + term;
+            let c = if sum.abs() >= term {
+                c + ((sum - t) + term)
+            } else {
+                c + ((term - t) + sum)
+            };
+            (t, c)
+        });
+        r + (sum + compensation).ln_1p()
+    }
+}
+
+///The result of [`LogSumExp::log_sum_exp_detailed`], which packages the (clamped) logsumexp
+///with some details of the reduction.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LogSumExpResult<T> {
+    value: LogProb<T>,
+    max: LogProb<T>,
+    count: usize,
+    overflowed: bool,
+}
+
+impl<T: Float> LogSumExpResult<T> {
+    ///The logsumexp, clamped at 0.0.
+    pub fn value(&self) -> LogProb<T> {
+        self.value
+    }
+
+    ///The largest element, or an impossible [`LogProb`] if there were no elements.
+    pub fn max(&self) -> LogProb<T> {
+        self.max
+    }
+
+    ///The number of elements.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    ///Whether the sum was greater than 0.0 and had to be clamped.
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+}
+
 ///This trait allows iterators to have `LogSumExp`.
 pub trait LogSumExp: Iterator {
     ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a new `Result<LogProb,
@@ -141,8 +593,38 @@ pub trait LogSumExp: Iterator {
         }
     }
 
+    ///Maps each element of the iterator to a [`LogProb`] with `f` and adds them up (as raw
+    ///probabilities), like [`Self::log_sum_exp_no_alloc`]. The map is fused into the reduction so
+    ///nothing is allocated.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let z = (1..=2)
+    ///     .log_sum_exp_by(|x: i32| LogProb::from_raw_prob(0.25 * f64::from(x)).unwrap())
+    ///     .unwrap();
+    /// assert_eq!(z, LogProb::from_raw_prob(0.75).unwrap());
+    /// ```
+    fn log_sum_exp_by<T: Float + Ln2, F: FnMut(Self::Item) -> LogProb<T>>(
+        self,
+        f: F,
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>
+    where
+        Self: Sized,
+    {
+        self.map(f).log_sum_exp_no_alloc()
+    }
+
     ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a new [`LogProb`] clamping values greater than 0.0.
     ///Will only return `Ok` if the sum could be a valid [`LogProb`]. It does not allocate a vector and will often be faster than [`log_sum_exp_clamped`] if you expect there to be clamping as the iterator can short-circuit.
+    ///
+    ///The iterator stops being consumed as soon as the running sum exceeds 1.0, so if it is
+    ///sorted in descending order, the overflow is found after as few elements as possible.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = [LogProb::new(-0.4_f64).unwrap(); 10];
+    /// let mut pulled = 0;
+    /// let sum = x.iter().inspect(|_| pulled += 1).log_sum_exp_clamped_no_alloc();
+    /// assert_eq!((sum, pulled), (LogProb::certain(), 2));
+    /// ```
     fn log_sum_exp_clamped_no_alloc<T: Float + Ln2, L: Borrow<LogProb<T>>>(mut self) -> LogProb<T>
     where
         Self: Sized,
@@ -159,6 +641,45 @@ pub trait LogSumExp: Iterator {
         }
     }
 
+    ///Like [`Self::log_sum_exp_clamped_no_alloc`], but assumes the iterator is sorted in
+    ///descending order. Since the first element is then the largest, it is used to rescale the
+    ///rest, so each element costs a single `exp` rather than a [`logaddexp`], and the tail of the
+    ///iterator is not consumed once the sum exceeds 1.0. The ordering is only checked with a debug
+    ///assertion.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = [0.75, 0.5, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// assert_eq!(x.iter().log_sum_exp_clamped_sorted_desc(), LogProb::certain());
+    /// ```
+    fn log_sum_exp_clamped_sorted_desc<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        mut self,
+    ) -> LogProb<T>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let max = match self.next() {
+            Some(x) if x.borrow().0 != T::NEG_INFINITY => x.borrow().0,
+            _ => return LogProb(T::NEG_INFINITY),
+        };
+        // The sum exceeds 1.0 once Σ exp(x - max) exceeds exp(-max).
+        let limit = (-max).exp();
+        let sum = self.try_fold((T::one(), max), |(acc, prev), x| {
+            let x = x.borrow().0;
+            debug_assert!(x <= prev, "input is not sorted in descending order");
+            let acc = acc + (x - max).exp();
+            if acc > limit {
+                Err(())
+            } else {
+                Ok((acc, x))
+            }
+        });
+        match sum {
+            Ok((acc, _)) => clamp_log_prob(acc.ln() + max),
+            Err(()) => LogProb(T::ZERO),
+        }
+    }
+
     ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a float with their sum,
     ///regardless of if it would be a valid [`LogProb`]. It does not allocate a vector.
     fn log_sum_exp_float_no_alloc<T: Float + Ln2, L: Borrow<LogProb<T>>>(mut self) -> T
@@ -190,6 +711,190 @@ pub trait LogSumExp: Iterator {
         Ok(LogProb::new(log_sum_exp_allocate_inner(self))?)
     }
 
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a [`LogSumExpResult`]
+    ///with the sum clamped at 0.0, along with the largest element, the number of elements, and
+    ///whether the sum had to be clamped. It does allocate a vector.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = [0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let result = x.iter().log_sum_exp_detailed();
+    /// assert_eq!(result.value(), LogProb::from_raw_prob(0.75).unwrap());
+    /// assert_eq!((result.max(), result.count(), result.overflowed()), (x[1], 2, false));
+    /// ```
+    fn log_sum_exp_detailed<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> LogSumExpResult<T>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let mut max = LogProb(T::NEG_INFINITY);
+        let v: Vec<LogProb<T>> = self
+            .map(|x| {
+                let x = *x.borrow();
+                if x > max {
+                    max = x;
+                }
+                x
+            })
+            .collect();
+        let total = log_sum_exp_inner(&v, max);
+        LogSumExpResult {
+            value: clamp_log_prob(total),
+            max,
+            count: v.len(),
+            overflowed: total > T::zero(),
+        }
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) like [`Self::log_sum_exp`], but
+    ///returns `None` for an empty iterator, to tell apart having no elements from elements
+    ///which sum to zero probability.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// assert_eq!(std::iter::empty::<LogProb<f64>>().log_sum_exp_nonempty(), None);
+    /// assert_eq!(
+    ///     [LogProb::<f64>::impossible()].iter().log_sum_exp_nonempty(),
+    ///     Some(Ok(LogProb::impossible()))
+    /// );
+    /// ```
+    fn log_sum_exp_nonempty<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> Option<Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let mut iter = self.peekable();
+        iter.peek()?;
+        Some(iter.log_sum_exp())
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) like [`Self::log_sum_exp`], but
+    ///for an [`ExactSizeIterator`], so the vector is allocated once with exactly `self.len()`
+    ///elements rather than grown as the iterator is consumed.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = [0.5, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// assert_eq!(x.iter().log_sum_exp_exact(), x.iter().log_sum_exp());
+    /// ```
+    fn log_sum_exp_exact<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>
+    where
+        Self: Sized,
+        Self: ExactSizeIterator<Item = L>,
+    {
+        let mut v = Vec::with_capacity(self.len());
+        let mut max = LogProb(T::NEG_INFINITY);
+        v.extend(self.map(|x| {
+            let x = *x.borrow();
+            if x > max {
+                max = x;
+            }
+            x
+        }));
+        Ok(LogProb::new(log_sum_exp_inner(&v, max))?)
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) like [`Self::log_sum_exp`], but
+    ///refines the first-pass result `r` with one correction step, `r + ln(1 + residual)` where the
+    ///residual `Σ exp(x_i - r) - 1` is computed in a second pass with compensated summation. This
+    ///removes most of the rounding error accumulated by the first pass when adding up many
+    ///terms. It does allocate a vector.
+    fn log_sum_exp_refined<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let v: Vec<T> = self.map(|x| x.borrow().0).collect();
+        let max = v.iter().fold(T::NEG_INFINITY, |acc, &x| acc.max(x));
+        if max.is_infinite() {
+            return Ok(LogProb::new(max)?);
+        }
+        // First pass: r = max + ln(s).
+        let s = v.iter().fold(T::ZERO, |acc, &x| acc + (x - max).exp());
+        // Second pass: exp(x_i - r) = exp(x_i - max) / s, so the residual is (Σ exp(x_i - max) - s) / s,
+        // accumulated with Neumaier summation starting from -s.
+        let (sum, compensation) = v.iter().fold((-s, T::ZERO), |(sum, c), &x| {
+            let term = (x - max).exp();
+            let t = sum + term;
+            let c = if sum.abs() >= term {
+                c + ((sum - t) + term)
+            } else {
+                c + ((term - t) + sum)
+            };
+            (t, c)
+        });
+        let r = s.ln() + max;
+        Ok(LogProb::new(r + ((sum + compensation) / s).ln_1p())?)
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) along with `seed`, e.g. a running
+    ///total from previous batches, which is included as an extra term in the reduction. Will only
+    ///return `Ok` if the sum could be a valid [`LogProb`]. It does allocate a vector.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = LogProb::from_raw_prob(0.25_f64).unwrap();
+    /// let total = [x, x].iter().log_sum_exp_with_seed(x).unwrap();
+    /// approx::assert_relative_eq!(total.raw_prob(), 0.75);
+    /// ```
+    fn log_sum_exp_with_seed<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        self,
+        seed: LogProb<T>,
+    ) -> Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let v: Vec<T> = std::iter::once(seed.0)
+            .chain(self.map(|x| x.borrow().0))
+            .collect();
+        Ok(LogProb::new(log_sum_exp_float_slice(&v))?)
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) like [`Self::log_sum_exp`], and
+    ///also returns a conservative estimate of the absolute error of the result (in log space).
+    ///The estimate is `ε * (n + 2 + range + |result|)`, where `ε` is the machine epsilon, `n` is
+    ///the number of terms and `range` is the difference between the largest and smallest
+    ///possible terms. Rounding while summing grows with `n`, while rounding while rescaling
+    ///terms grows with their dynamic range, so a large estimate suggests a compensated method
+    ///like [`Self::log_sum_exp_refined`] may be worthwhile. It does allocate a vector.
+    /// ```
+    /// # use logprob::{LogProb, LogSumExp};
+    /// let x = LogProb::from_raw_prob(0.25_f64).unwrap();
+    /// let (sum, error) = [x, x].iter().log_sum_exp_with_error();
+    /// assert_eq!(sum.unwrap(), LogProb::from_raw_prob(0.5).unwrap());
+    /// assert!(error < 1e-14);
+    /// ```
+    fn log_sum_exp_with_error<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> (Result<LogProb<T>, ProbabilitiesSumToGreaterThanOne>, T)
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let v: Vec<T> = self.map(|x| x.borrow().0).collect();
+        let result = log_sum_exp_float_slice(&v);
+        let (min, max) = v
+            .iter()
+            .filter(|x| x.is_finite())
+            .fold((T::infinity(), T::NEG_INFINITY), |(min, max), &x| {
+                (min.min(x), max.max(x))
+            });
+        let range = if max >= min { max - min } else { T::ZERO };
+        let n = T::from(v.len()).unwrap();
+        let two = T::one() + T::one();
+        let error = if result.is_finite() {
+            T::epsilon() * (n + two + range + result.abs())
+        } else {
+            T::ZERO
+        };
+        (LogProb::new(result).map_err(|e| e.into()), error)
+    }
+
     ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a float with their sum,
     ///regardless of if it would be a valid [`LogProb`]. It does allocate a vector, but is usally
     ///slower than [`Self::log_sum_exp_clamped_no_alloc`] if you expect clamping.
@@ -206,6 +911,38 @@ pub trait LogSumExp: Iterator {
         }
     }
 
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a [`LogProb`] clamping
+    ///values greater than 0.0, along with a flag which is `true` if clamping occurred. It does
+    ///allocate a vector.
+    fn log_sum_exp_clamped_reported<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> (LogProb<T>, bool)
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        match LogProb::new(log_sum_exp_allocate_inner(self)) {
+            Ok(x) => (x, false),
+            Err(_) => (LogProb(T::ZERO), true),
+        }
+    }
+
+    ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a [`LogProb`] clamping
+    ///values greater than 0.0, along with a `Result` which is a [`ProbabilitiesSumToGreaterThanOne`]
+    ///error if clamping was necessary. It does allocate a vector.
+    fn log_sum_exp_or_clamp<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> (LogProb<T>, Result<(), ProbabilitiesSumToGreaterThanOne>)
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        match LogProb::new(log_sum_exp_allocate_inner(self)) {
+            Ok(x) => (x, Ok(())),
+            Err(_) => (LogProb(T::ZERO), Err(ProbabilitiesSumToGreaterThanOne)),
+        }
+    }
+
     ///Adds up an iterator of [`LogProb`] (as raw probabilities) and returns a float with their sum,
     ///regardless of if it would be a valid [`LogProb`]. It does allocate a vector, but will usually be faster for n>10.
     fn log_sum_exp_float<T: Float + Ln2 + std::iter::Sum, L: Borrow<LogProb<T>>>(self) -> T
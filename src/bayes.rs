@@ -0,0 +1,77 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{normalize_in_place, Ln2},
+    Float, LengthMismatch, LogProb,
+};
+
+///Computes the posterior distribution from a `prior` and a `likelihood`, where `posterior_i ∝
+///prior_i * likelihood_i`. An impossible prior or likelihood makes that posterior impossible. If
+///every posterior is impossible (i.e. the evidence has zero probability) they are all returned as
+///impossible. Returns a [`LengthMismatch`] if the slices have different lengths.
+/// ```
+/// # use logprob::{LogProb, bayesian_update};
+/// let prior = [0.5, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let likelihood = [0.3, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let posterior = bayesian_update(&prior, &likelihood).unwrap();
+/// approx::assert_relative_eq!(posterior[0].raw_prob(), 0.75);
+/// ```
+pub fn bayesian_update<T: Float + Ln2 + Sum>(
+    prior: &[LogProb<T>],
+    likelihood: &[LogProb<T>],
+) -> Result<Vec<LogProb<T>>, LengthMismatch> {
+    if prior.len() != likelihood.len() {
+        return Err(LengthMismatch);
+    }
+    let mut posterior: Vec<_> = prior.iter().zip(likelihood).map(|(&p, &l)| p + l).collect();
+    normalize_in_place(&mut posterior);
+    Ok(posterior)
+}
+
+///A belief over a set of categories which is updated online with a stream of likelihoods. The
+///belief is renormalized after every update to avoid underflow, and the cumulative log-evidence
+///(the log-probability of every likelihood seen so far) is tracked for model comparison.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BeliefState<T> {
+    belief: Vec<LogProb<T>>,
+    log_evidence: T,
+}
+
+impl<T: Float + Ln2> BeliefState<T> {
+    ///Creates a new [`BeliefState`] from a `prior`, which is normalized.
+    pub fn new(prior: &[LogProb<T>]) -> Self {
+        let mut belief = prior.to_vec();
+        normalize_in_place(&mut belief);
+        BeliefState {
+            belief,
+            log_evidence: T::ZERO,
+        }
+    }
+
+    ///Multiplies the belief by `likelihood` and renormalizes it. Returns a [`LengthMismatch`] if
+    ///`likelihood` has a different number of categories than the belief.
+    pub fn update(&mut self, likelihood: &[LogProb<T>]) -> Result<(), LengthMismatch> {
+        if likelihood.len() != self.belief.len() {
+            return Err(LengthMismatch);
+        }
+        self.belief
+            .iter_mut()
+            .zip(likelihood)
+            .for_each(|(b, &l)| *b = *b + l);
+        self.log_evidence = self.log_evidence + normalize_in_place(&mut self.belief);
+        Ok(())
+    }
+
+    ///The current belief.
+    #[inline]
+    pub fn belief(&self) -> &[LogProb<T>] {
+        &self.belief
+    }
+
+    ///The cumulative log-evidence of every update so far, i.e. the log-probability of the
+    ///observed likelihoods under the prior.
+    #[inline]
+    pub fn log_evidence(&self) -> T {
+        self.log_evidence
+    }
+}
@@ -0,0 +1,118 @@
+use super::{
+    adding::{log_sum_exp_float_slice, normalize_in_place, Ln2},
+    as_float_slice, CategoricalError, Float, LogProb,
+};
+
+///A categorical distribution, i.e. a slice of [`LogProb`] which is guaranteed to sum to one (as
+///raw probabilities), up to an absolute tolerance of `1e-6` on the total.
+/// ```
+/// # use logprob::{Categorical, LogProb};
+/// let dist = Categorical::try_from(vec![0.25_f64, 0.75]).unwrap();
+/// assert_eq!(dist.probs()[0], LogProb::from_raw_prob(0.25).unwrap());
+/// assert!(Categorical::try_from(vec![0.25_f64, 0.5]).is_err());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Categorical<T> {
+    probs: Vec<LogProb<T>>,
+}
+
+impl<T: Float + Ln2> Categorical<T> {
+    ///Construct a new [`Categorical`], returning a [`CategoricalError::NotNormalized`] error if
+    ///`probs` do not sum to one.
+    pub fn new(probs: Vec<LogProb<T>>) -> Result<Self, CategoricalError> {
+        let total = log_sum_exp_float_slice(as_float_slice(&probs));
+        if (total.exp() - T::one()).abs() <= T::from(1e-6).unwrap() {
+            Ok(Categorical { probs })
+        } else {
+            Err(CategoricalError::NotNormalized)
+        }
+    }
+
+    ///Gets the probabilities of each category.
+    #[inline]
+    pub fn probs(&self) -> &[LogProb<T>] {
+        &self.probs
+    }
+
+    ///The number of categories.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.probs.len()
+    }
+
+    ///Checks if there are no categories (which is never the case, as an empty distribution
+    ///doesn't sum to one).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.probs.is_empty()
+    }
+
+    ///Returns an iterator over the probabilities of each category.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, LogProb<T>> {
+        self.probs.iter()
+    }
+
+    ///Get the probabilities out.
+    #[inline]
+    pub fn into_inner(self) -> Vec<LogProb<T>> {
+        self.probs
+    }
+}
+
+impl<T: Float + Ln2> TryFrom<Vec<T>> for Categorical<T> {
+    type Error = CategoricalError;
+
+    ///Converts raw probabilities into a [`Categorical`], returning the index of the first invalid
+    ///probability or an error if they do not sum to one.
+    fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
+        let probs = value
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                LogProb::from_raw_prob(x).map_err(|_| CategoricalError::InvalidProbability(i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Categorical::new(probs)
+    }
+}
+
+impl<T> IntoIterator for Categorical<T> {
+    type Item = LogProb<T>;
+    type IntoIter = std::vec::IntoIter<LogProb<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.probs.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Categorical<T> {
+    type Item = &'a LogProb<T>;
+    type IntoIter = std::slice::Iter<'a, LogProb<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.probs.iter()
+    }
+}
+
+impl<T: Float + Ln2> FromIterator<LogProb<T>> for Categorical<T> {
+    ///Collects [`LogProb`] into a [`Categorical`], renormalizing them so that they sum to one.
+    ///This allows transforming a distribution with e.g. `dist.into_iter().map(...).collect()`.
+    ///
+    ///# Panics
+    ///Panics if no element is possible, since it can't be renormalized.
+    /// ```
+    /// # use logprob::{Categorical, LogProb};
+    /// let dist = Categorical::try_from(vec![0.25_f64, 0.75]).unwrap();
+    /// let squared: Categorical<f64> = dist.into_iter().map(|x| x + x).collect();
+    /// approx::assert_relative_eq!(squared.probs()[0].raw_prob(), 0.1);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = LogProb<T>>>(iter: I) -> Self {
+        let mut probs: Vec<LogProb<T>> = iter.into_iter().collect();
+        assert!(
+            normalize_in_place(&mut probs) != T::NEG_INFINITY,
+            "cannot renormalize a distribution with no possible elements"
+        );
+        Categorical { probs }
+    }
+}
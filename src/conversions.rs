@@ -0,0 +1,171 @@
+#[cfg(feature = "num-rational")]
+use num_rational::Ratio;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use std::iter::Sum;
+
+use super::{
+    adding::{normalize_in_place, Ln2},
+    Float, FloatIsNanOrPositive, FloorError, LogProb,
+};
+
+///Reinterprets a slice of [`LogProb`] as a slice of their underlying floats without copying.
+///This is possible since [`LogProb`] is `#[repr(transparent)]`.
+/// ```
+/// # use logprob::{LogProb, as_float_slice};
+/// let v = [LogProb::new(-1.0).unwrap(), LogProb::new(-2.0).unwrap()];
+/// assert_eq!(as_float_slice(&v), &[-1.0, -2.0]);
+/// ```
+pub fn as_float_slice<T>(vals: &[LogProb<T>]) -> &[T] {
+    // SAFETY: LogProb<T> is repr(transparent) over T, so the two slices have the same layout.
+    unsafe { std::slice::from_raw_parts(vals.as_ptr().cast::<T>(), vals.len()) }
+}
+
+///Reinterprets a slice of floats as a slice of [`LogProb`] without copying, after checking every
+///element is a valid [`LogProb`]. On failure, returns the index of the first invalid element.
+pub fn from_float_slice<T: Float>(
+    vals: &[T],
+) -> Result<&[LogProb<T>], (usize, FloatIsNanOrPositive)> {
+    if let Some((i, err)) = vals
+        .iter()
+        .enumerate()
+        .find_map(|(i, &x)| LogProb::new(x).err().map(|err| (i, err)))
+    {
+        return Err((i, err));
+    }
+    // SAFETY: LogProb<T> is repr(transparent) over T and every element has been validated.
+    Ok(unsafe { std::slice::from_raw_parts(vals.as_ptr().cast::<LogProb<T>>(), vals.len()) })
+}
+
+///Converts `f64` raw probabilities into `LogProb<f32>`. The logarithm is taken in `f64` before
+///narrowing, so tiny probabilities below the range of `f32` (e.g. `1e-40`) still become finite
+///log-probabilities rather than `-inf`. On failure, returns the index of the first invalid
+///probability.
+pub fn from_raw_probs_f64_to_f32(
+    vals: &[f64],
+) -> Result<Vec<LogProb<f32>>, (usize, FloatIsNanOrPositive)> {
+    vals.iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            LogProb::from_raw_prob(x)
+                .and_then(|x| LogProb::new(x.0 as f32))
+                .map_err(|err| (i, err))
+        })
+        .collect()
+}
+
+///Narrows a distribution of `LogProb<f64>` to `LogProb<f32>`, returning the indices of any
+///elements which became impossible because they were finite but below the range of `f32`.
+/// ```
+/// # use logprob::{LogProb, narrow_to_f32};
+/// let x = [LogProb::new(-1.0).unwrap(), LogProb::new(-1e300).unwrap()];
+/// let (y, lost) = narrow_to_f32(&x);
+/// assert_eq!(y[0], LogProb::new(-1.0_f32).unwrap());
+/// assert_eq!(lost, vec![1]);
+/// ```
+pub fn narrow_to_f32(vals: &[LogProb<f64>]) -> (Vec<LogProb<f32>>, Vec<usize>) {
+    let mut underflowed = vec![];
+    let narrowed = vals
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let y = x.0 as f32;
+            if y == f32::NEG_INFINITY && x.0.is_finite() {
+                underflowed.push(i);
+            }
+            LogProb(y)
+        })
+        .collect();
+    (narrowed, underflowed)
+}
+
+///Converts the raw probability at index `i`, tagging any error with its index.
+#[inline]
+fn raw_prob_at<T: Float>(i: usize, x: T) -> Result<LogProb<T>, (usize, FloatIsNanOrPositive)> {
+    LogProb::from_raw_prob(x).map_err(|err| (i, err))
+}
+
+///Converts a slice of raw probabilities into [`LogProb`]. On failure, returns the index of the
+///first invalid probability.
+/// ```
+/// # use logprob::{LogProb, from_raw_probs};
+/// let v = from_raw_probs(&[0.5_f64, 1.0]).unwrap();
+/// assert_eq!(v[1], LogProb::certain());
+/// assert_eq!(from_raw_probs(&[0.5_f64, 2.0, -1.0]).unwrap_err().0, 1);
+/// ```
+pub fn from_raw_probs<T: Float>(
+    vals: &[T],
+) -> Result<Vec<LogProb<T>>, (usize, FloatIsNanOrPositive)> {
+    vals.iter()
+        .enumerate()
+        .map(|(i, &x)| raw_prob_at(i, x))
+        .collect()
+}
+
+///Converts a slice of raw probabilities into a proper distribution of [`LogProb`] in which every
+///probability is at least `floor` before renormalizing, so that no element is impossible. Returns
+///[`FloorError::InvalidFloor`] if `floor` is not in `(0, 1]`, and otherwise the index of the first
+///invalid probability on failure.
+/// ```
+/// # use logprob::{LogProb, FloorError, from_raw_probs_floored};
+/// let v = from_raw_probs_floored(&[0.0_f64, 0.5, 0.5], 0.01).unwrap();
+/// approx::assert_relative_eq!(v[0].raw_prob(), 0.01 / 1.01);
+/// assert_eq!(from_raw_probs_floored(&[0.5_f64], 0.0), Err(FloorError::InvalidFloor));
+/// ```
+pub fn from_raw_probs_floored<T: Float + Ln2 + Sum>(
+    vals: &[T],
+    floor: T,
+) -> Result<Vec<LogProb<T>>, FloorError> {
+    if !(floor > T::zero() && floor <= T::one()) {
+        return Err(FloorError::InvalidFloor);
+    }
+    let mut probs = vals
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            raw_prob_at(i, x)
+                .map(|_| LogProb(x.max(floor).ln()))
+                .map_err(|(i, _)| FloorError::InvalidProbability(i))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    normalize_in_place(&mut probs);
+    Ok(probs)
+}
+
+///Converts a slice of raw probabilities into [`LogProb`] in parallel with `rayon`. Like
+///[`from_raw_probs`], on failure, returns the index of the first invalid probability regardless
+///of how the work was split.
+#[cfg(feature = "rayon")]
+pub fn par_from_raw_probs<T: Float + Send + Sync>(
+    vals: &[T],
+) -> Result<Vec<LogProb<T>>, (usize, FloatIsNanOrPositive)> {
+    vals.par_iter()
+        .enumerate()
+        .map(|(i, &x)| raw_prob_at(i, x))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            vals.par_iter()
+                .position_first(|&x| LogProb::from_raw_prob(x).is_err())
+                .map_or(err, |i| (i, FloatIsNanOrPositive))
+        })
+}
+
+#[cfg(feature = "num-rational")]
+impl LogProb<f64> {
+    ///Construct a new [`LogProb`] from an exact fraction in \[0,1\]. This is handy for setting up
+    ///distributions with small denominators, e.g. in tests. Returns a [`FloatIsNanOrPositive`]
+    ///error if the fraction is negative or greater than one.
+    /// ```
+    /// # use logprob::LogProb;
+    /// use num_rational::Ratio;
+    /// let x = LogProb::from_ratio(Ratio::new(1, 4)).unwrap();
+    /// assert_eq!(x, LogProb::from_raw_prob(0.25).unwrap());
+    /// ```
+    pub fn from_ratio(r: Ratio<i64>) -> Result<Self, FloatIsNanOrPositive> {
+        if *r.numer() < 0 || r.numer() > r.denom() {
+            return Err(FloatIsNanOrPositive);
+        }
+        LogProb::from_raw_prob(*r.numer() as f64 / *r.denom() as f64)
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Float, FloatIsNan, LogProb};
+
+///A wrapper for the log of a probability density. Unlike a [`LogProb`], a density can be greater
+///than 1.0, so a [`LogDensity`] can store any float other than NaN.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct LogDensity<T>(T);
+
+impl<T: Float> LogDensity<T> {
+    /// Construct a new [`LogDensity`] that will return an error if `val` is NaN.
+    #[inline]
+    pub fn new(val: T) -> Result<Self, FloatIsNan> {
+        if val.is_nan() {
+            Err(FloatIsNan)
+        } else {
+            Ok(LogDensity(val))
+        }
+    }
+
+    /// Get the value out.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// Get the equivalent non-log density.
+    #[inline]
+    pub fn density(&self) -> T {
+        self.0.exp()
+    }
+}
+
+impl<T: Float> From<LogProb<T>> for LogDensity<T> {
+    #[inline]
+    fn from(p: LogProb<T>) -> Self {
+        LogDensity(p.0)
+    }
+}
+
+impl<T: Float + std::fmt::Display> std::fmt::Display for LogDensity<T> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+fn expect_not_nan<T: Float>(val: T) -> LogDensity<T> {
+    LogDensity::new(val).expect("log-density is NaN, are any of the arguments NaN?")
+}
+
+///Calculates the log-density of a normal distribution with mean `mean` and standard deviation
+///`std` at `x`.
+///
+///# Panics
+///Panics if `std` is not positive or if any argument is NaN.
+/// ```
+/// # use logprob::normal_log_pdf;
+/// let x = normal_log_pdf(0.0_f64, 0.0, 1.0);
+/// approx::assert_relative_eq!(x.into_inner(), -0.5 * std::f64::consts::TAU.ln());
+/// ```
+pub fn normal_log_pdf<T: Float>(x: T, mean: T, std: T) -> LogDensity<T> {
+    assert!(std > T::zero(), "standard deviation must be positive");
+    let z = (x - mean) / std;
+    let half = T::from(0.5).unwrap();
+    let log_tau = T::from(std::f64::consts::TAU).unwrap().ln();
+    expect_not_nan(-half * z * z - std.ln() - half * log_tau)
+}
+
+///Calculates the log-density of a Cauchy distribution with location `loc` and scale `scale` at
+///`x`. Far out in the tails, `ln(1 + z^2)` is computed as `2 ln|z| + ln(1 + 1/z^2)` so that it
+///doesn't overflow.
+///
+///# Panics
+///Panics if `scale` is not positive or if any argument is NaN.
+/// ```
+/// # use logprob::cauchy_log_pdf;
+/// let x = cauchy_log_pdf(0.0_f64, 0.0, 1.0);
+/// approx::assert_relative_eq!(x.into_inner(), -std::f64::consts::PI.ln());
+/// ```
+pub fn cauchy_log_pdf<T: Float>(x: T, loc: T, scale: T) -> LogDensity<T> {
+    assert!(scale > T::zero(), "scale must be positive");
+    let z = ((x - loc) / scale).abs();
+    let log_1p_z2 = if z > T::one() {
+        let two = T::from(2.0).unwrap();
+        two * z.ln() + (z * z).recip().ln_1p()
+    } else {
+        (z * z).ln_1p()
+    };
+    let log_pi = T::from(std::f64::consts::PI).unwrap().ln();
+    expect_not_nan(-log_pi - scale.ln() - log_1p_z2)
+}
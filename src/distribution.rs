@@ -0,0 +1,98 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, normalize_in_place, Ln2},
+    Float, InvalidTemperature, LogProb,
+};
+
+///Normalizes `vals` (as raw probabilities) into a proper distribution, regardless of whether their
+///total is below or above one. Unlike [`log_sum_exp`](super::log_sum_exp), which errors if the
+///total is greater than one, this always rescales, clamping at 0.0. If every element is
+///impossible, there is nothing to rescale and they are returned as they are.
+/// ```
+/// # use logprob::{LogProb, to_proper_distribution};
+/// let x = [0.75, 0.75].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let y = to_proper_distribution(&x);
+/// assert_eq!(y, vec![LogProb::from_raw_prob(0.5).unwrap(); 2]);
+/// ```
+pub fn to_proper_distribution<T: Float + Ln2 + Sum>(vals: &[LogProb<T>]) -> Vec<LogProb<T>> {
+    let mut vals = vals.to_vec();
+    normalize_in_place(&mut vals);
+    vals
+}
+
+///Convolves two distributions in log space, so that the `k`th element is the logsumexp of
+///`a_i + b_j` over all `i + j = k`. This is the distribution of the sum of two independent
+///discrete random variables. The result has length `a.len() + b.len() - 1` (or is empty if either
+///input is) and is clamped at 0.0.
+/// ```
+/// # use logprob::{LogProb, log_convolve};
+/// let coin = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+/// let heads = log_convolve(&coin, &coin);
+/// assert_eq!(heads[1], LogProb::from_raw_prob(0.5).unwrap());
+/// ```
+pub fn log_convolve<T: Float + Ln2 + Sum>(a: &[LogProb<T>], b: &[LogProb<T>]) -> Vec<LogProb<T>> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let n = a.len() + b.len() - 1;
+    let mut terms = Vec::with_capacity(a.len().min(b.len()));
+    (0..n)
+        .map(|k| {
+            let lo = k.saturating_sub(b.len() - 1);
+            let hi = k.min(a.len() - 1);
+            terms.clear();
+            terms.extend((lo..=hi).map(|i| a[i].0 + b[k - i].0));
+            clamp_log_prob(log_sum_exp_float_slice(&terms))
+        })
+        .collect()
+}
+
+///Writes the complement (see [`LogProb::opposite_prob`]) of each element of `vals` into `out`,
+///replacing its previous contents. Reusing `out` across calls avoids allocating a new vector each
+///time.
+/// ```
+/// # use logprob::{LogProb, opposite_probs_into};
+/// let x = [0.25, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let mut out = vec![];
+/// opposite_probs_into(&x, &mut out);
+/// assert_eq!(out, vec![LogProb::from_raw_prob(0.75).unwrap(), LogProb::impossible()]);
+/// ```
+pub fn opposite_probs_into<T: Float + Ln2>(vals: &[LogProb<T>], out: &mut Vec<LogProb<T>>) {
+    out.clear();
+    out.extend(vals.iter().map(LogProb::opposite_prob));
+}
+
+///Tempers a distribution by dividing each log-probability by `temperature` and renormalizing.
+///Temperatures below 1.0 sharpen the distribution and temperatures above 1.0 flatten it, so that
+///as the temperature goes to infinity it approaches the uniform distribution over the possible
+///events. Impossible events stay impossible. The result is a proper distribution unless every
+///event is impossible, in which case it is returned as it is.
+///
+///Returns an [`InvalidTemperature`] error if `temperature` is not positive or is NaN.
+/// ```
+/// # use logprob::{LogProb, temper};
+/// let x = [0.2, 0.8].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let y = temper(&x, 0.5).unwrap();
+/// approx::assert_relative_eq!(y[0].raw_prob(), 0.04 / 0.68);
+/// ```
+pub fn temper<T: Float + Ln2 + Sum>(
+    vals: &[LogProb<T>],
+    temperature: T,
+) -> Result<Vec<LogProb<T>>, InvalidTemperature> {
+    if temperature.is_nan() || temperature <= T::ZERO {
+        return Err(InvalidTemperature);
+    }
+    let mut tempered: Vec<LogProb<T>> = vals
+        .iter()
+        .map(|x| {
+            if x.0 == T::NEG_INFINITY {
+                *x
+            } else {
+                LogProb(x.0 / temperature)
+            }
+        })
+        .collect();
+    normalize_in_place(&mut tempered);
+    Ok(tempered)
+}
@@ -0,0 +1,105 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, Ln2},
+    Float, LengthMismatch, LogProb,
+};
+
+fn check_lengths<T>(p: &[LogProb<T>], q: &[LogProb<T>]) -> Result<(), LengthMismatch> {
+    if p.len() == q.len() {
+        Ok(())
+    } else {
+        Err(LengthMismatch)
+    }
+}
+
+///Calculates the Kullback-Leibler divergence, `KL(p || q)`, in nats. Entries where `p_i` is
+///impossible contribute nothing, while entries where `q_i` is impossible but `p_i` is not make
+///the divergence infinite. Returns a [`LengthMismatch`] if the slices have different lengths.
+pub fn kl_divergence<T: Float + Ln2>(
+    p: &[LogProb<T>],
+    q: &[LogProb<T>],
+) -> Result<T, LengthMismatch> {
+    check_lengths(p, q)?;
+    Ok(p.iter()
+        .zip(q)
+        .filter(|(p, _)| p.0 != T::NEG_INFINITY)
+        .fold(T::ZERO, |acc, (p, q)| acc + p.raw_prob() * (p.0 - q.0)))
+}
+
+///Calculates the Rényi divergence of order `alpha` (which should be non-negative),
+///`1/(alpha-1) * ln Σ p_i^alpha q_i^(1-alpha)`, in nats. When `alpha` is 1.0, this is the
+///[`kl_divergence`]. Impossible entries follow the usual conventions, so only the support of `p`
+///is summed over and entries where `q_i` is impossible make the divergence infinite when
+///`alpha > 1`. Returns a [`LengthMismatch`] if the slices have different lengths.
+pub fn renyi_divergence<T: Float + Ln2 + Sum>(
+    p: &[LogProb<T>],
+    q: &[LogProb<T>],
+    alpha: T,
+) -> Result<T, LengthMismatch> {
+    check_lengths(p, q)?;
+    if alpha == T::one() {
+        return kl_divergence(p, q);
+    }
+    let mut terms = Vec::with_capacity(p.len());
+    for (p, q) in p.iter().zip(q) {
+        if p.0 == T::NEG_INFINITY {
+            continue;
+        } else if q.0 == T::NEG_INFINITY {
+            if alpha > T::one() {
+                return Ok(T::infinity());
+            }
+        } else {
+            terms.push(alpha * p.0 + (T::one() - alpha) * q.0);
+        }
+    }
+    Ok(log_sum_exp_float_slice(&terms) / (alpha - T::one()))
+}
+
+///Calculates the total variation distance, `0.5 * Σ |p_i - q_i|`, between two distributions as
+///raw probabilities. Since this works in probability space, impossible entries are exactly zero
+///and need no special casing. Returns a [`LengthMismatch`] if the slices have different lengths.
+pub fn total_variation_distance<T: Float>(
+    p: &[LogProb<T>],
+    q: &[LogProb<T>],
+) -> Result<T, LengthMismatch> {
+    check_lengths(p, q)?;
+    let two = T::one() + T::one();
+    Ok(p.iter()
+        .zip(q)
+        .fold(T::zero(), |acc, (p, q)| acc + p.prob_abs_diff(q))
+        / two)
+}
+
+///Calculates the Bhattacharyya coefficient, `Σ sqrt(p_i q_i)`, which lies in \[0, 1\] and so is
+///returned as a [`LogProb`]. It is computed stably with a logsumexp over `(p_i + q_i) / 2`, so
+///impossible entries drop out. Returns a [`LengthMismatch`] if the slices have different
+///lengths.
+pub fn bhattacharyya<T: Float + Ln2 + Sum>(
+    p: &[LogProb<T>],
+    q: &[LogProb<T>],
+) -> Result<LogProb<T>, LengthMismatch> {
+    check_lengths(p, q)?;
+    let two = T::one() + T::one();
+    let terms: Vec<T> = p.iter().zip(q).map(|(p, q)| (p.0 + q.0) / two).collect();
+    Ok(clamp_log_prob(log_sum_exp_float_slice(&terms)))
+}
+
+///Calculates the Hellinger distance, `sqrt(1 - BC)`, where `BC` is the [`bhattacharyya`]
+///coefficient. Returns a [`LengthMismatch`] if the slices have different lengths.
+pub fn hellinger_distance<T: Float + Ln2 + Sum>(
+    p: &[LogProb<T>],
+    q: &[LogProb<T>],
+) -> Result<T, LengthMismatch> {
+    let coefficient = bhattacharyya(p, q)?.raw_prob();
+    Ok((T::one() - coefficient).max(T::zero()).sqrt())
+}
+
+///Calculates the elementwise log likelihood ratios, `ln(p_i / q_i) = p_i - q_i`, as floats (which
+///may be positive). If both `p_i` and `q_i` are impossible the ratio is undefined and is `NaN`,
+///while if only `q_i` is impossible it is positive infinity. Returns a [`LengthMismatch`] if the
+///slices have different lengths.
+pub fn log_ratios<T: Float>(p: &[LogProb<T>], q: &[LogProb<T>]) -> Result<Vec<T>, LengthMismatch> {
+    check_lengths(p, q)?;
+    Ok(p.iter().zip(q).map(|(p, q)| p.0 - q.0).collect())
+}
@@ -0,0 +1,111 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{log_sum_exp_float_slice, Ln2},
+    DimensionMismatch, Float, LogProb, LogProbMatrix,
+};
+
+///The family of entropies computed by [`generalized_entropy`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EntropyKind<T> {
+    ///The Shannon entropy, `-Σ p_i ln p_i`.
+    Shannon,
+    ///The Rényi entropy of order `alpha` (which should be non-negative),
+    ///`1/(1-alpha) * ln Σ p_i^alpha`. Orders of 1.0 and infinity give the Shannon and
+    ///min-entropy respectively.
+    Renyi(T),
+    ///The Tsallis entropy of order `q`, `(1 - Σ p_i^q) / (q-1)`. An order of 1.0 gives the
+    ///Shannon entropy.
+    Tsallis(T),
+    ///The min-entropy, `-ln max p_i`.
+    Min,
+    ///The collision entropy, `-ln Σ p_i^2`, i.e. the Rényi entropy of order 2.
+    Collision,
+}
+
+///Calculates the entropy of the distribution `vals`, in nats, for any of the [`EntropyKind`].
+///Only the support of `vals` is summed over, so impossible elements contribute nothing. If no
+///element is possible, the entropy is 0.0.
+/// ```
+/// # use logprob::{LogProb, EntropyKind, generalized_entropy};
+/// let x = [LogProb::from_raw_prob(0.25_f64).unwrap(); 4];
+/// let h = generalized_entropy(&x, EntropyKind::Shannon);
+/// approx::assert_relative_eq!(h, 4.0_f64.ln());
+/// approx::assert_relative_eq!(generalized_entropy(&x, EntropyKind::Renyi(3.0)), h);
+/// ```
+pub fn generalized_entropy<T: Float + Ln2 + Sum>(vals: &[LogProb<T>], kind: EntropyKind<T>) -> T {
+    let support: Vec<T> = vals
+        .iter()
+        .map(|x| x.0)
+        .filter(|&x| x != T::NEG_INFINITY)
+        .collect();
+    if support.is_empty() {
+        return T::ZERO;
+    }
+    let power_sum = |order: T| {
+        let terms: Vec<T> = support.iter().map(|&x| order * x).collect();
+        log_sum_exp_float_slice(&terms)
+    };
+    match kind {
+        EntropyKind::Shannon => support.iter().map(|&x| -x.exp() * x).sum(),
+        EntropyKind::Renyi(alpha) if alpha == T::one() => {
+            generalized_entropy(vals, EntropyKind::Shannon)
+        }
+        EntropyKind::Renyi(alpha) if alpha == T::infinity() => {
+            generalized_entropy(vals, EntropyKind::Min)
+        }
+        EntropyKind::Renyi(alpha) => power_sum(alpha) / (T::one() - alpha),
+        EntropyKind::Tsallis(q) if q == T::one() => generalized_entropy(vals, EntropyKind::Shannon),
+        EntropyKind::Tsallis(q) => -power_sum(q).exp_m1() / (q - T::one()),
+        EntropyKind::Min => -support.iter().fold(T::NEG_INFINITY, |a, &b| a.max(b)),
+        EntropyKind::Collision => -power_sum(T::one() + T::one()),
+    }
+}
+
+///Calculates the Gini impurity of the distribution `vals`, `1 - Σ p_i^2`, from the logsumexp of
+///`2 * lp_i`. Impossible elements are skipped, and if no element is possible, the impurity is
+///0.0.
+/// ```
+/// # use logprob::{LogProb, gini_impurity};
+/// let x = [LogProb::from_raw_prob(0.25_f64).unwrap(); 4];
+/// approx::assert_relative_eq!(gini_impurity(&x), 0.75);
+/// ```
+pub fn gini_impurity<T: Float + Ln2 + Sum>(vals: &[LogProb<T>]) -> T {
+    let squares: Vec<T> = vals
+        .iter()
+        .filter(|x| x.0 != T::NEG_INFINITY)
+        .map(|x| x.0 + x.0)
+        .collect();
+    if squares.is_empty() {
+        return T::ZERO;
+    }
+    -log_sum_exp_float_slice(&squares).exp_m1()
+}
+
+///Calculates the entropy rate of a Markov chain, `Σ_i π_i Σ_j P_ij ln(1/P_ij)`, in nats, from its
+///stationary distribution `π` and its transition matrix `P`, whose rows are the distributions
+///over the next state. This is the conditional entropy of each row weighted by the stationary
+///distribution, and impossible transitions (and states) contribute nothing. Returns a
+///[`DimensionMismatch`] if the matrix isn't square or doesn't match the stationary distribution.
+/// ```
+/// # use logprob::{LogProb, LogProbMatrix, entropy_rate};
+/// let half = LogProb::from_raw_prob(0.5_f64).unwrap();
+/// let transitions = LogProbMatrix::new(2, 2, vec![half; 4]).unwrap();
+/// approx::assert_relative_eq!(entropy_rate(&[half, half], &transitions).unwrap(), 2.0_f64.ln());
+/// ```
+pub fn entropy_rate<T: Float + Ln2 + Sum>(
+    stationary: &[LogProb<T>],
+    transitions: &LogProbMatrix<T>,
+) -> Result<T, DimensionMismatch> {
+    if transitions.rows() != transitions.cols() || transitions.rows() != stationary.len() {
+        return Err(DimensionMismatch);
+    }
+    Ok(stationary
+        .iter()
+        .enumerate()
+        .filter(|(_, pi)| pi.0 != T::NEG_INFINITY)
+        .map(|(i, pi)| {
+            pi.raw_prob() * generalized_entropy(transitions.row(i), EntropyKind::Shannon)
+        })
+        .sum())
+}
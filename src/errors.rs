@@ -1,6 +1,8 @@
 use std::error::Error;
+
+use serde::{Deserialize, Serialize};
 /// An error for when a [`LogProb`](super::LogProb) is passed a value that isn't negative.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct FloatIsNanOrPositive;
 
 impl Error for FloatIsNanOrPositive {}
@@ -12,7 +14,7 @@ impl std::fmt::Display for FloatIsNanOrPositive {
 }
 
 /// An error for when a [`LogProb`](super::LogProb)  is passed a value that isn't negative.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct ProbabilitiesSumToGreaterThanOne;
 
 impl Error for ProbabilitiesSumToGreaterThanOne {}
@@ -30,7 +32,7 @@ impl From<FloatIsNanOrPositive> for ProbabilitiesSumToGreaterThanOne {
 }
 
 /// An error for when a [`Softmax`] is passed a value that is NaN or infinity.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct FloatIsNanOrPositiveInfinity;
 
 impl Error for FloatIsNanOrPositiveInfinity {}
@@ -40,3 +42,205 @@ impl std::fmt::Display for FloatIsNanOrPositiveInfinity {
         write!(f, "LogProb constructed with positive or NaN value")
     }
 }
+
+/// An error for when matrices or vectors have dimensions that don't agree.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DimensionMismatch;
+
+impl Error for DimensionMismatch {}
+
+impl std::fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The dimensions of the inputs do not match")
+    }
+}
+
+/// An error for when two slices which should be aligned have different lengths.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct LengthMismatch;
+
+impl Error for LengthMismatch {}
+
+impl std::fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The inputs have different lengths")
+    }
+}
+
+/// An error for when a conditional distribution can't be extracted from a joint distribution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ConditioningError {
+    /// The dimensions of the joint distribution do not match its length.
+    DimensionMismatch,
+    /// The row to condition on is out of bounds.
+    RowOutOfBounds,
+    /// The row to condition on has zero probability.
+    ImpossibleCondition,
+}
+
+impl Error for ConditioningError {}
+
+impl std::fmt::Display for ConditioningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConditioningError::DimensionMismatch => {
+                write!(f, "The dimensions of the inputs do not match")
+            }
+            ConditioningError::RowOutOfBounds => write!(f, "The conditioned row is out of bounds"),
+            ConditioningError::ImpossibleCondition => {
+                write!(f, "Can't condition on an event with zero probability")
+            }
+        }
+    }
+}
+
+impl From<DimensionMismatch> for ConditioningError {
+    fn from(_value: DimensionMismatch) -> Self {
+        ConditioningError::DimensionMismatch
+    }
+}
+
+/// An error for when [`from_raw_probs_floored`](super::from_raw_probs_floored) can't build a
+/// distribution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum FloorError {
+    /// The floor is not in `(0, 1]`.
+    InvalidFloor,
+    /// The element at this index is not a valid probability.
+    InvalidProbability(usize),
+}
+
+impl Error for FloorError {}
+
+impl std::fmt::Display for FloorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FloorError::InvalidFloor => write!(f, "The floor must be in (0, 1]"),
+            FloorError::InvalidProbability(i) => {
+                write!(f, "The element at index {i} is not a valid probability")
+            }
+        }
+    }
+}
+
+/// An error for when a [`LogDensity`](super::LogDensity) is passed a NaN value.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct FloatIsNan;
+
+impl Error for FloatIsNan {}
+
+impl std::fmt::Display for FloatIsNan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LogDensity constructed with NaN value")
+    }
+}
+
+/// An error for when a temperature is not positive (or is NaN).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct InvalidTemperature;
+
+impl Error for InvalidTemperature {}
+
+impl std::fmt::Display for InvalidTemperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The temperature must be positive")
+    }
+}
+
+/// An error for when an input contains a value which should have been rejected at construction,
+/// e.g. a NaN in a [`LogProb`](super::LogProb) made with
+/// [`LogProb::new_unchecked`](super::LogProb::new_unchecked).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct InvalidInput;
+
+impl Error for InvalidInput {}
+
+impl std::fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The input contains a NaN value")
+    }
+}
+
+/// An error for when [`log_sum_exp_checked`](super::log_sum_exp_checked) can't add up a slice.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LogSumExpError {
+    /// The slice contains a NaN value.
+    InvalidInput,
+    /// The probabilities sum to greater than one.
+    ProbabilitiesSumToGreaterThanOne,
+}
+
+impl Error for LogSumExpError {}
+
+impl std::fmt::Display for LogSumExpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogSumExpError::InvalidInput => InvalidInput.fmt(f),
+            LogSumExpError::ProbabilitiesSumToGreaterThanOne => {
+                ProbabilitiesSumToGreaterThanOne.fmt(f)
+            }
+        }
+    }
+}
+
+impl From<InvalidInput> for LogSumExpError {
+    fn from(_value: InvalidInput) -> Self {
+        LogSumExpError::InvalidInput
+    }
+}
+
+impl From<ProbabilitiesSumToGreaterThanOne> for LogSumExpError {
+    fn from(_value: ProbabilitiesSumToGreaterThanOne) -> Self {
+        LogSumExpError::ProbabilitiesSumToGreaterThanOne
+    }
+}
+
+/// An error for when a [`Categorical`](super::Categorical) distribution can't be constructed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CategoricalError {
+    /// The element at this index is not a valid probability.
+    InvalidProbability(usize),
+    /// The probabilities do not sum to one.
+    NotNormalized,
+}
+
+impl Error for CategoricalError {}
+
+impl std::fmt::Display for CategoricalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CategoricalError::InvalidProbability(i) => {
+                write!(f, "The element at index {i} is not a valid probability")
+            }
+            CategoricalError::NotNormalized => write!(f, "The probabilities do not sum to one"),
+        }
+    }
+}
+
+/// An error for when a [`LogProb`](super::LogProb) can't be parsed from a string.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ParseLogProbError {
+    /// The string is not a float.
+    NotAFloat,
+    /// The string is a float, but it is positive or NaN.
+    NanOrPositive,
+}
+
+impl Error for ParseLogProbError {}
+
+impl std::fmt::Display for ParseLogProbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseLogProbError::NotAFloat => write!(f, "The string is not a valid float"),
+            ParseLogProbError::NanOrPositive => {
+                write!(f, "LogProb parsed from a positive or NaN value")
+            }
+        }
+    }
+}
+
+impl From<FloatIsNanOrPositive> for ParseLogProbError {
+    fn from(_value: FloatIsNanOrPositive) -> Self {
+        ParseLogProbError::NanOrPositive
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Display;
+
+use num_traits::float::TotalOrder;
+
+use super::{ranking::argsort_desc, Float, LogProb};
+
+///Formats a labelled distribution as a table with one `label: prob (logprob)` row per element,
+///sorted from most to least probable. Labels are left-aligned and both probability columns are
+///right-aligned, so the columns line up. Impossible elements are shown with a probability of `0`.
+/// ```
+/// # use logprob::{LogProb, format_distribution};
+/// let v = [0.25, 0.75].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let table = format_distribution(&["a", "bcd"], &v);
+/// assert_eq!(
+///     table,
+///     format!("bcd: 0.75 ({})\na  : 0.25 ({})", 0.75_f64.ln(), 0.25_f64.ln())
+/// );
+/// ```
+///
+///# Panics
+///Panics if `labels` and `vals` have different lengths.
+pub fn format_distribution<T: Float + TotalOrder + Display, L: Display>(
+    labels: &[L],
+    vals: &[LogProb<T>],
+) -> String {
+    assert_eq!(
+        labels.len(),
+        vals.len(),
+        "labels and distribution have different lengths"
+    );
+    let labels: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+    let probs: Vec<String> = vals.iter().map(|x| x.raw_prob().to_string()).collect();
+    let logs: Vec<String> = vals.iter().map(|x| format!("({x})")).collect();
+    let column_width = |col: &[String]| col.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+    let (width, prob_width, log_width) = (
+        column_width(&labels),
+        column_width(&probs),
+        column_width(&logs),
+    );
+    argsort_desc(vals)
+        .into_iter()
+        .map(|i| {
+            format!(
+                "{:<width$}: {:>prob_width$} {:>log_width$}",
+                labels[i], probs[i], logs[i]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,127 @@
+use std::{cmp::Ordering, iter::Sum};
+
+use num_traits::float::TotalOrder;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, Ln2},
+    DimensionMismatch, Float, LogProb, LogProbMatrix,
+};
+
+fn check_hmm_dimensions<T: Float>(
+    initial: &[LogProb<T>],
+    transitions: &LogProbMatrix<T>,
+    emissions: &[Vec<LogProb<T>>],
+) -> Result<(), DimensionMismatch> {
+    let n = initial.len();
+    if transitions.rows() != n || transitions.cols() != n || emissions.iter().any(|e| e.len() != n)
+    {
+        Err(DimensionMismatch)
+    } else {
+        Ok(())
+    }
+}
+
+///Runs the scaled forward algorithm of a hidden Markov model in log space and returns the total
+///log-likelihood of the observations. `initial` is the distribution of the first state,
+///`transitions[(i, j)]` is the probability of going from state `i` to state `j` and
+///`emissions[t][j]` is the probability of the `t`th observation given state `j`.
+///
+///Returns a [`DimensionMismatch`] if the number of states is inconsistent between arguments.
+pub fn hmm_forward<T: Float + Ln2 + Sum>(
+    initial: &[LogProb<T>],
+    transitions: &LogProbMatrix<T>,
+    emissions: &[Vec<LogProb<T>>],
+) -> Result<LogProb<T>, DimensionMismatch> {
+    check_hmm_dimensions(initial, transitions, emissions)?;
+    let n = initial.len();
+    let mut alpha: Vec<T> = initial.iter().map(|x| x.0).collect();
+    let mut next = Vec::with_capacity(n);
+    let mut terms = Vec::with_capacity(n);
+    let mut log_likelihood = T::ZERO;
+
+    for (t, emission) in emissions.iter().enumerate() {
+        if t > 0 {
+            next.clear();
+            for j in 0..n {
+                terms.clear();
+                terms.extend((0..n).map(|i| alpha[i] + transitions[(i, j)].0));
+                next.push(log_sum_exp_float_slice(&terms));
+            }
+            std::mem::swap(&mut alpha, &mut next);
+        }
+        alpha
+            .iter_mut()
+            .zip(emission.iter())
+            .for_each(|(a, e)| *a = *a + e.0);
+
+        let scale = log_sum_exp_float_slice(&alpha);
+        if scale == T::NEG_INFINITY {
+            return Ok(LogProb(T::NEG_INFINITY));
+        }
+        alpha.iter_mut().for_each(|a| *a = *a - scale);
+        log_likelihood = log_likelihood + scale;
+    }
+
+    if emissions.is_empty() {
+        log_likelihood = log_sum_exp_float_slice(&alpha);
+    }
+    Ok(clamp_log_prob(log_likelihood))
+}
+
+///Returns the index and value of the first maximum by `total_cmp` so that ties break to the lower
+///index.
+fn first_max<T: Float + TotalOrder, I: Iterator<Item = LogProb<T>>>(
+    iter: I,
+) -> Option<(usize, LogProb<T>)> {
+    iter.enumerate().fold(None, |best, (i, x)| match best {
+        Some((_, b)) if x.0.total_cmp(&b.0) != Ordering::Greater => best,
+        _ => Some((i, x)),
+    })
+}
+
+///Finds the most probable sequence of hidden states of a hidden Markov model with Viterbi
+///decoding and returns it along with its log-probability. The arguments are the same as in
+///[`hmm_forward`]. Scores are compared with `total_cmp` and ties are broken in favour of the lower
+///state index.
+///
+///# Panics
+///Panics if the number of states is inconsistent between arguments.
+pub fn viterbi<T: Float + TotalOrder>(
+    initial: &[LogProb<T>],
+    transitions: &LogProbMatrix<T>,
+    emissions: &[Vec<LogProb<T>>],
+) -> (Vec<usize>, LogProb<T>) {
+    assert!(
+        check_hmm_dimensions(initial, transitions, emissions).is_ok(),
+        "inconsistent number of states"
+    );
+    let n = initial.len();
+    let Some((first, rest)) = emissions.split_first() else {
+        return (vec![], LogProb(T::zero()));
+    };
+    if n == 0 {
+        return (vec![], LogProb(T::neg_infinity()));
+    }
+
+    let mut delta: Vec<LogProb<T>> = initial.iter().zip(first).map(|(&x, &e)| x + e).collect();
+    let mut backpointers: Vec<Vec<usize>> = Vec::with_capacity(rest.len());
+    for emission in rest {
+        let (pointers, next): (Vec<usize>, Vec<LogProb<T>>) = (0..n)
+            .map(|j| {
+                let (i, best) = first_max((0..n).map(|i| delta[i] + transitions[(i, j)])).unwrap();
+                (i, best + emission[j])
+            })
+            .unzip();
+        backpointers.push(pointers);
+        delta = next;
+    }
+
+    let (mut state, best) = first_max(delta.into_iter()).unwrap();
+    let mut path = vec![state];
+    for pointers in backpointers.iter().rev() {
+        state = pointers[state];
+        path.push(state);
+    }
+    path.reverse();
+    (path, best)
+}
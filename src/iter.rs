@@ -0,0 +1,200 @@
+use std::borrow::Borrow;
+
+use super::{
+    adding::{clamp_log_prob, Ln2},
+    logaddexp, Float, LogProb,
+};
+
+///This trait adds adapters to iterators of [`LogProb`].
+pub trait LogProbIterator: Iterator {
+    ///Multiplies (as raw probabilities) each element with the corresponding element of `prior`,
+    ///yielding unnormalized posteriors. It stops at the shorter of the two.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let likelihood = [0.5, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let prior = [0.5, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let posterior: Vec<_> = likelihood.iter().apply_log_prior(&prior).collect();
+    /// assert_eq!(posterior[0], LogProb::from_raw_prob(0.25).unwrap());
+    /// ```
+    fn apply_log_prior<T: Float, L: Borrow<LogProb<T>>>(
+        self,
+        prior: &[LogProb<T>],
+    ) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        self.zip(prior).map(|(x, p)| *x.borrow() + *p)
+    }
+
+    ///Multiplies (as raw probabilities) each element with the corresponding element of `other`,
+    ///e.g. to combine two independent per-position likelihoods. It stops at the shorter of the
+    ///two.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let x = [0.5, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let y = [0.5, 1.0, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let z: Vec<_> = x.iter().zip_log_mul(y).collect();
+    /// assert_eq!(z, [0.25, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap()));
+    /// ```
+    fn zip_log_mul<T: Float, L: Borrow<LogProb<T>>, I: IntoIterator<Item = LogProb<T>>>(
+        self,
+        other: I,
+    ) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        self.zip(other).map(|(x, y)| *x.borrow() + y)
+    }
+
+    ///Yields only the elements which are possible, dropping any whose log-probability is
+    ///negative infinity.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let x = [-1.0, f64::NEG_INFINITY, -0.5].map(|x| LogProb::new(x).unwrap());
+    /// assert_eq!(x.iter().filter_possible().count(), 2);
+    /// ```
+    fn filter_possible<T: Float, L: Borrow<LogProb<T>>>(self) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        self.map(|x| *x.borrow())
+            .filter(|x| x.0 != T::neg_infinity())
+    }
+
+    ///Collapses runs of consecutive near-equal elements (e.g. in a sorted iterator), yielding the
+    ///first element of each run. An element belongs to the current run if its log-probability is
+    ///within `tol` of the run's first element, so a run can't drift further than `tol`.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let x = [-1.0, -1.001, -1.002, -2.0].map(|x| LogProb::new(x).unwrap());
+    /// let y: Vec<_> = x.iter().dedup_by_prob(0.01).collect();
+    /// assert_eq!(y, vec![x[0], x[3]]);
+    /// ```
+    fn dedup_by_prob<T: Float, L: Borrow<LogProb<T>>>(
+        self,
+        tol: T,
+    ) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let mut representative: Option<LogProb<T>> = None;
+        self.filter_map(move |x| {
+            let x = *x.borrow();
+            match representative {
+                Some(r) if x == r || (x.0 - r.0).abs() <= tol => None,
+                _ => {
+                    representative = Some(x);
+                    Some(x)
+                }
+            }
+        })
+    }
+
+    ///Yields each element's share of the total seen so far (as raw probabilities), i.e. the
+    ///element minus the running logsumexp including it. The last element is therefore normalized
+    ///with respect to the whole iterator. While everything seen so far is impossible, the share
+    ///is impossible.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let x = [0.25, 0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// let shares: Vec<_> = x.iter().running_normalized().map(|x| x.raw_prob()).collect();
+    /// assert_eq!(shares, vec![1.0, 0.5, 0.5]);
+    /// ```
+    fn running_normalized<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        self,
+    ) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        self.scan(T::NEG_INFINITY, |total, x| {
+            let x = x.borrow().0;
+            *total = logaddexp(*total, x);
+            Some(if *total == T::NEG_INFINITY {
+                LogProb(T::NEG_INFINITY)
+            } else {
+                clamp_log_prob(x - *total)
+            })
+        })
+    }
+
+    ///Yields elements (e.g. of a stream sorted in descending order) until their cumulative mass
+    ///first reaches `p` (as a raw probability), including the element which reaches it, and then
+    ///stops without pulling any further elements. This is the lazy version of
+    ///[`split_at_mass`](crate::split_at_mass), so nothing is yielded if `p` is at most 0.0.
+    /// ```
+    /// # use logprob::{LogProb, LogProbIterator};
+    /// let x = [0.5, 0.3, 0.15, 0.05].map(|x| LogProb::from_raw_prob(x).unwrap());
+    /// assert_eq!(x.iter().take_while_mass(0.7).count(), 2);
+    /// ```
+    fn take_while_mass<T: Float + Ln2, L: Borrow<LogProb<T>>>(
+        self,
+        p: T,
+    ) -> impl Iterator<Item = LogProb<T>>
+    where
+        Self: Sized,
+        Self: Iterator<Item = L>,
+    {
+        let target = if p > T::zero() {
+            p.ln()
+        } else {
+            T::NEG_INFINITY
+        };
+        let mut total = T::NEG_INFINITY;
+        let mut iter = self;
+        std::iter::from_fn(move || {
+            if total >= target {
+                return None;
+            }
+            let x = *iter.next()?.borrow();
+            total = logaddexp(total, x.0);
+            Some(x)
+        })
+    }
+}
+
+impl<I: ?Sized> LogProbIterator for I where I: Iterator {}
+
+///Yields `n` evenly spaced log-probabilities from `start` to `end` (inclusive), spaced linearly in
+///log space. If either bound is impossible, spacing in log space isn't defined, so the points are
+///instead spaced linearly in probability space (so that e.g. the points between impossible and
+///certain are evenly spaced probabilities). If `n` is 1, only `start` is yielded.
+/// ```
+/// # use logprob::{LogProb, log_prob_linspace};
+/// let start = LogProb::new(-2.0_f64).unwrap();
+/// let end = LogProb::new(0.0).unwrap();
+/// let grid: Vec<_> = log_prob_linspace(start, end, 3).map(|x| x.into_inner()).collect();
+/// assert_eq!(grid, vec![-2.0, -1.0, 0.0]);
+///
+/// let grid: Vec<_> = log_prob_linspace(LogProb::impossible(), end, 3)
+///     .map(|x| x.raw_prob())
+///     .collect();
+/// assert_eq!(grid, vec![0.0, 0.5, 1.0]);
+/// ```
+pub fn log_prob_linspace<T: Float>(
+    start: LogProb<T>,
+    end: LogProb<T>,
+    n: usize,
+) -> impl Iterator<Item = LogProb<T>> {
+    let in_prob_space = start.0 == T::neg_infinity() || end.0 == T::neg_infinity();
+    let steps = T::from(n.saturating_sub(1).max(1)).unwrap();
+    (0..n).map(move |i| {
+        if i == 0 {
+            return start;
+        } else if i == n - 1 {
+            return end;
+        }
+        let t = T::from(i).unwrap() / steps;
+        let x = if in_prob_space {
+            let (a, b) = (start.raw_prob(), end.raw_prob());
+            (a + (b - a) * t).ln()
+        } else {
+            start.0 + (end.0 - start.0) * t
+        };
+        LogProb(x.min(T::zero()))
+    })
+}
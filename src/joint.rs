@@ -0,0 +1,108 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, normalize_in_place, Ln2},
+    ConditioningError, DimensionMismatch, Float, LogProb,
+};
+
+type Marginals<T> = (Vec<LogProb<T>>, Vec<LogProb<T>>);
+
+fn check_joint_dimensions<T>(
+    joint: &[LogProb<T>],
+    rows: usize,
+    cols: usize,
+) -> Result<(), DimensionMismatch> {
+    if rows.checked_mul(cols) == Some(joint.len()) {
+        Ok(())
+    } else {
+        Err(DimensionMismatch)
+    }
+}
+
+///Calculates both marginals of a joint distribution stored row-major as a `rows` by `cols` grid,
+///returning the row marginal (summing over columns) and the column marginal (summing over rows).
+///Sums are clamped at 0.0 so that rounding can't produce an invalid [`LogProb`]. Returns a
+///[`DimensionMismatch`] if `rows * cols != joint.len()`.
+/// ```
+/// # use logprob::{LogProb, marginals};
+/// let quarter = LogProb::from_raw_prob(0.25_f64).unwrap();
+/// let (row, col) = marginals(&[quarter; 4], 2, 2).unwrap();
+/// assert_eq!(row, vec![LogProb::from_raw_prob(0.5).unwrap(); 2]);
+/// assert_eq!(row, col);
+/// ```
+pub fn marginals<T: Float + Ln2 + Sum>(
+    joint: &[LogProb<T>],
+    rows: usize,
+    cols: usize,
+) -> Result<Marginals<T>, DimensionMismatch> {
+    check_joint_dimensions(joint, rows, cols)?;
+    let mut terms = Vec::with_capacity(rows.max(cols));
+    let row_marginal = (0..rows)
+        .map(|i| {
+            terms.clear();
+            terms.extend(joint[i * cols..(i + 1) * cols].iter().map(|x| x.0));
+            clamp_log_prob(log_sum_exp_float_slice(&terms))
+        })
+        .collect();
+    let col_marginal = (0..cols)
+        .map(|j| {
+            terms.clear();
+            terms.extend((0..rows).map(|i| joint[i * cols + j].0));
+            clamp_log_prob(log_sum_exp_float_slice(&terms))
+        })
+        .collect();
+    Ok((row_marginal, col_marginal))
+}
+
+///Calculates the mutual information, `Σ p(x,y) * (log p(x,y) - log p(x) - log p(y))`, in nats
+///between the rows and columns of a joint distribution stored row-major as a `rows` by `cols`
+///grid. Impossible joint entries contribute nothing. For a valid joint distribution, this is
+///non-negative (up to rounding) and zero when the rows and columns are independent. Returns a
+///[`DimensionMismatch`] if `rows * cols != joint.len()`.
+pub fn mutual_information<T: Float + Ln2 + Sum>(
+    joint: &[LogProb<T>],
+    rows: usize,
+    cols: usize,
+) -> Result<T, DimensionMismatch> {
+    let (row_marginal, col_marginal) = marginals(joint, rows, cols)?;
+    Ok(joint
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.0 != T::NEG_INFINITY)
+        .fold(T::ZERO, |acc, (k, p)| {
+            let (i, j) = (k / cols, k % cols);
+            acc + p.raw_prob() * (p.0 - row_marginal[i].0 - col_marginal[j].0)
+        }))
+}
+
+///Extracts the conditional distribution over columns given the row `given_row` of a joint
+///distribution stored row-major as a `rows` by `cols` grid, by normalizing that row.
+///
+///Returns a [`ConditioningError`] if `rows * cols != joint.len()`, if `given_row` is out of
+///bounds or if the row has zero probability.
+/// ```
+/// # use logprob::{LogProb, conditional};
+/// let joint: Vec<LogProb<f64>> = [0.1, 0.3, 0.2, 0.4]
+///     .into_iter()
+///     .map(|p| LogProb::from_raw_prob(p).unwrap())
+///     .collect();
+/// let y_given_x = conditional(&joint, 2, 2, 0).unwrap();
+/// approx::assert_relative_eq!(y_given_x[0].raw_prob(), 0.25);
+/// approx::assert_relative_eq!(y_given_x[1].raw_prob(), 0.75);
+/// ```
+pub fn conditional<T: Float + Ln2 + Sum>(
+    joint: &[LogProb<T>],
+    rows: usize,
+    cols: usize,
+    given_row: usize,
+) -> Result<Vec<LogProb<T>>, ConditioningError> {
+    check_joint_dimensions(joint, rows, cols)?;
+    if given_row >= rows {
+        return Err(ConditioningError::RowOutOfBounds);
+    }
+    let mut row = joint[given_row * cols..(given_row + 1) * cols].to_vec();
+    if normalize_in_place(&mut row) == T::NEG_INFINITY {
+        return Err(ConditioningError::ImpossibleCondition);
+    }
+    Ok(row)
+}
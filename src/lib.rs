@@ -96,21 +96,91 @@ use std::borrow::Borrow;
 use num_traits::Float;
 mod errors;
 pub use errors::{
-    FloatIsNanOrPositive, FloatIsNanOrPositiveInfinity, ProbabilitiesSumToGreaterThanOne,
+    CategoricalError, ConditioningError, DimensionMismatch, FloatIsNan, FloatIsNanOrPositive,
+    FloatIsNanOrPositiveInfinity, FloorError, InvalidInput, InvalidTemperature, LengthMismatch,
+    LogSumExpError, ParseLogProbError, ProbabilitiesSumToGreaterThanOne,
 };
 use serde::{Deserialize, Serialize};
 mod adding;
+mod bayes;
+mod categorical;
+mod conversions;
+mod density;
+mod distribution;
+mod divergence;
+mod entropy;
+mod format;
+mod hmm;
+mod iter;
+mod joint;
+mod loss;
 mod math;
+mod matrix;
+mod ordering;
+mod prefix;
+mod ranking;
+#[cfg(feature = "rand")]
+mod sampling;
+mod sequence;
+pub mod serde_raw_prob;
+#[cfg(feature = "simd")]
+mod simd;
 mod softmax;
-pub use softmax::{softmax, Softmax};
+mod stats;
+mod support;
+pub use bayes::{bayesian_update, BeliefState};
+pub use categorical::Categorical;
+#[cfg(feature = "rayon")]
+pub use conversions::par_from_raw_probs;
+pub use conversions::{
+    as_float_slice, from_float_slice, from_raw_probs, from_raw_probs_f64_to_f32,
+    from_raw_probs_floored, narrow_to_f32,
+};
+pub use density::{cauchy_log_pdf, normal_log_pdf, LogDensity};
+pub use distribution::{log_convolve, opposite_probs_into, temper, to_proper_distribution};
+pub use divergence::{
+    bhattacharyya, hellinger_distance, kl_divergence, log_ratios, renyi_divergence,
+    total_variation_distance,
+};
+pub use entropy::{entropy_rate, generalized_entropy, gini_impurity, EntropyKind};
+pub use format::format_distribution;
+pub use hmm::{hmm_forward, viterbi};
+pub use iter::{log_prob_linspace, LogProbIterator};
+pub use joint::{conditional, marginals, mutual_information};
+pub use loss::{cross_entropy_logit_grad, label_smoothed_nll};
+pub use matrix::{log_matmul, LogProbMatrix};
+pub use ordering::{LogProbApprox, MinProb};
+pub use prefix::{log_cumsum_exp, split_at_mass, PrefixLogSumExp};
+pub use ranking::{argsort_desc, confidence, partition_above, top_k_mask};
+#[cfg(feature = "rand")]
+pub use sampling::{sample_tempered, weighted_reservoir_sample};
+pub use sequence::{joint_log_prob, sequence_nll, RollingPerplexity};
+#[cfg(feature = "simd")]
+pub use simd::{log_sum_exp_clamped_f32, softmax_f32_fast};
+pub use softmax::{argmax_logit, from_logits, softmax, softmax_clamped, softmax_jvp, Softmax};
+pub use stats::{mass_in_range, prob_histogram, weighted_median, weighted_quantiles, OnlineMean};
+pub use support::{renormalize_support, support_indices, support_size};
 
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Serialize, Deserialize)]
 #[repr(transparent)]
-
 ///Struct that can only hold float values that correspond to negative log
 ///probabilities.
+///
+///Equality and ordering follow the underlying floats, which is guaranteed: 0.0 and -0.0 are
+///equal (both are certain), negative infinity is equal to itself (impossible events are all
+///equal) and finite values are equal only if their floats are. Since NaN can't be stored, this
+///is a total order which is consistent with [`Eq`] and [`Ord`].
+/// ```
+/// # use logprob::LogProb;
+/// assert_eq!(LogProb::new(0.0_f64).unwrap(), LogProb::new(-0.0).unwrap());
+/// assert_eq!(LogProb::<f64>::impossible(), LogProb::impossible());
+/// ```
 pub struct LogProb<T>(T);
-pub use adding::{log_sum_exp, log_sum_exp_clamped, log_sum_exp_float, LogSumExp};
+pub use adding::{
+    log_sum_exp, log_sum_exp_checked, log_sum_exp_clamped, log_sum_exp_f32, log_sum_exp_f64,
+    log_sum_exp_float, log_sum_exp_fn, log_sum_exp_groups, log_sum_exp_raw, logaddexp, logsubexp,
+    ChunkedLogSumExp, LogSumExp, LogSumExpBuilder, LogSumExpResult, MaybeSendSync,
+};
 
 impl<T: Float> LogProb<T> {
     ///Construct a new [`LogProb`] that is guaranteed to be negative (or +0.0).
@@ -122,6 +192,19 @@ impl<T: Float> LogProb<T> {
         }
     }
 
+    ///Construct a new [`LogProb`] without checking that `val` is negative (or +0.0), e.g. for
+    ///floats which have already been validated upstream. Use [`log_sum_exp_checked`] to catch NaN
+    ///which slipped through.
+    ///
+    ///# Safety
+    ///
+    ///`val` must be negative (or +0.0). Passing NaN or a positive value breaks the guarantees of
+    ///[`LogProb`], so other methods may panic or give meaningless results.
+    #[inline]
+    pub unsafe fn new_unchecked(val: T) -> Self {
+        LogProb(val)
+    }
+
     ///Construct a new [`LogProb`] that is guaranteed to be negative (or +0.0) from a value in [0.0, 1.0].
     pub fn from_raw_prob(val: T) -> Result<Self, FloatIsNanOrPositive> {
         let val = val.ln();
@@ -132,12 +215,66 @@ impl<T: Float> LogProb<T> {
         }
     }
 
+    ///Construct a new [`LogProb`] from a percentage in [0.0, 100.0], e.g. 25.0 for a probability
+    ///of 0.25. Returns a [`FloatIsNanOrPositive`] error for anything outside of that range.
+    /// ```
+    /// # use logprob::LogProb;
+    /// assert_eq!(LogProb::from_percent(25.0_f64), LogProb::from_raw_prob(0.25));
+    /// assert!(LogProb::from_percent(150.0_f64).is_err());
+    /// ```
+    pub fn from_percent(pct: T) -> Result<Self, FloatIsNanOrPositive> {
+        let hundred = T::from(100.0).unwrap();
+        if pct.is_nan() || pct < T::zero() || pct > hundred {
+            return Err(FloatIsNanOrPositive);
+        }
+        LogProb::from_raw_prob(pct / hundred)
+    }
+
+    ///Construct a [`LogProb`] of an event which is certain (i.e. 0.0).
+    #[inline]
+    pub fn certain() -> Self {
+        LogProb(T::zero())
+    }
+
+    ///Construct a [`LogProb`] of an event which is impossible (i.e. negative infinity).
+    #[inline]
+    pub fn impossible() -> Self {
+        LogProb(T::neg_infinity())
+    }
+
     /// Gets out the value.
     #[inline]
     pub fn into_inner(self) -> T {
         self.0
     }
 
+    /// Checks if this is the log-probability of an impossible event (i.e. negative infinity).
+    #[inline]
+    pub fn is_zero_prob(&self) -> bool {
+        self.0 == T::neg_infinity()
+    }
+
+    /// Checks if this is the log-probability of a certain event (i.e. either 0.0 or -0.0).
+    #[inline]
+    pub fn is_one_prob(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Gets both the log-probability and the equivalent non-log probability.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5_f64).unwrap();
+    /// let (logp, p) = x.as_pair();
+    /// assert_eq!(logp, 0.5_f64.ln());
+    /// assert_eq!(p, 0.5);
+    /// let (logp, p): (f64, f64) = x.into();
+    /// assert_eq!((logp, p), x.as_pair());
+    /// ```
+    #[inline]
+    pub fn as_pair(&self) -> (T, T) {
+        (self.0, self.raw_prob())
+    }
+
     /// Get the equivalent non-log probability
     /// ```
     /// # use logprob::LogProb;
@@ -159,6 +296,132 @@ impl<T: Float> LogProb<T> {
     pub fn opposite_prob(&self) -> Self {
         LogProb((-self.0.exp()).ln_1p())
     }
+
+    /// Takes the product of two probabilities, i.e. the joint probability of two independent
+    /// events. This is the same as `self + other`, but spells out that adding log-probabilities
+    /// multiplies their raw probabilities.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5).unwrap();
+    /// assert_eq!(x.times(&x), LogProb::from_raw_prob(0.25).unwrap());
+    /// ```
+    #[inline]
+    pub fn times(&self, other: &Self) -> Self {
+        LogProb(self.0 + other.0)
+    }
+
+    /// Takes the product of two probabilities (see [`LogProb::times`]) along with a flag which is
+    /// `true` if either operand is impossible, in which case the result is necessarily
+    /// impossible. This is a debugging aid to catch accidental multiplications by zero
+    /// probability.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5).unwrap();
+    /// assert_eq!(x.add_warn_absorbing(&x), (LogProb::from_raw_prob(0.25).unwrap(), false));
+    /// assert_eq!(
+    ///     x.add_warn_absorbing(&LogProb::impossible()),
+    ///     (LogProb::impossible(), true)
+    /// );
+    /// ```
+    #[inline]
+    pub fn add_warn_absorbing(&self, other: &Self) -> (Self, bool) {
+        let absorbed = self.is_zero_prob() || other.is_zero_prob();
+        (self.times(other), absorbed)
+    }
+
+    /// Raises the probability to the power of `n`, returning the log of the result, `self * n`, as
+    /// a float. Unlike multiplying by an unsigned integer, `n` can be negative, which gives the log
+    /// of a reciprocal. The result can therefore be positive, in which case it is not a valid
+    /// [`LogProb`]. Following `powi`, any probability to the power of 0 is 1.0 (so 0.0 is returned).
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5_f64).unwrap();
+    /// assert_eq!(x.powi(2), LogProb::from_raw_prob(0.25).unwrap().into_inner());
+    /// assert_eq!(x.powi(-1), 2.0_f64.ln());
+    /// ```
+    #[inline]
+    pub fn powi(self, n: i32) -> T {
+        if n == 0 {
+            return T::zero();
+        }
+        self.0 * T::from(n).unwrap()
+    }
+
+    /// Gets the absolute difference between two log-probabilities as raw probabilities.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.25).unwrap();
+    /// let y = LogProb::from_raw_prob(0.75).unwrap();
+    /// assert_eq!(x.prob_abs_diff(&y), 0.5);
+    /// ```
+    #[inline]
+    pub fn prob_abs_diff(&self, other: &Self) -> T {
+        (self.raw_prob() - other.raw_prob()).abs()
+    }
+
+    /// Checks if this log-probability is strictly more probable than `other`. This is the same
+    /// as `self > other`, but makes the direction of the comparison explicit.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5).unwrap();
+    /// let y = LogProb::from_raw_prob(0.1).unwrap();
+    /// assert!(x.is_more_probable_than(&y));
+    /// assert!(!y.is_more_probable_than(&x));
+    /// ```
+    #[inline]
+    pub fn is_more_probable_than(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Greater
+    }
+
+    /// Checks if this log-probability is strictly less probable than `other`. This is the same
+    /// as `self < other`, but makes the direction of the comparison explicit.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.1).unwrap();
+    /// let y = LogProb::from_raw_prob(0.5).unwrap();
+    /// assert!(x.is_less_probable_than(&y));
+    /// assert!(!LogProb::new(0.0).unwrap().is_less_probable_than(&LogProb::new(-0.0).unwrap()));
+    /// ```
+    #[inline]
+    pub fn is_less_probable_than(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Less
+    }
+
+    /// Quantizes the log-probability to `i16` fixed-point, where `scale` is the number of
+    /// quantization steps per unit of log-probability. Values too small to be represented
+    /// saturate at `i16::MIN + 1`, while `i16::MIN` is reserved for negative infinity. Within
+    /// range, the round-trip error of [`LogProb::dequantize`] is at most `1/scale`.
+    ///
+    /// `scale` should be positive and finite.
+    /// ```
+    /// # use logprob::LogProb;
+    /// let x = LogProb::from_raw_prob(0.5_f64).unwrap();
+    /// let q = x.quantize(1000.0);
+    /// assert_eq!(q, -693);
+    /// assert!((LogProb::dequantize(q, 1000.0).into_inner() - x.into_inner()).abs() <= 1e-3);
+    /// assert_eq!(LogProb::<f64>::impossible().quantize(1000.0), i16::MIN);
+    /// ```
+    #[inline]
+    pub fn quantize(&self, scale: T) -> i16 {
+        debug_assert!(scale > T::zero() && scale.is_finite());
+        if self.0 == T::neg_infinity() {
+            return i16::MIN;
+        }
+        let lowest = T::from(i16::MIN + 1).unwrap();
+        let q = (self.0 * scale).round().max(lowest).min(T::zero());
+        q.to_i16().unwrap_or(i16::MIN + 1)
+    }
+
+    /// Converts an `i16` produced by [`LogProb::quantize`] with the same `scale` back into a
+    /// [`LogProb`]. `i16::MIN` becomes negative infinity and positive values are clamped to 0.0.
+    #[inline]
+    pub fn dequantize(q: i16, scale: T) -> Self {
+        debug_assert!(scale > T::zero() && scale.is_finite());
+        if q == i16::MIN {
+            return LogProb(T::neg_infinity());
+        }
+        LogProb((T::from(q).unwrap() / scale).min(T::zero()))
+    }
 }
 
 impl<T: Float + std::fmt::Display> std::fmt::Display for LogProb<T> {
@@ -204,6 +467,13 @@ impl From<LogProb<f64>> for f64 {
         f.0
     }
 }
+impl<T: Float> From<LogProb<T>> for (T, T) {
+    #[inline]
+    fn from(f: LogProb<T>) -> (T, T) {
+        f.as_pair()
+    }
+}
+
 impl TryFrom<f64> for LogProb<f64> {
     type Error = FloatIsNanOrPositive;
 
@@ -219,3 +489,22 @@ impl TryFrom<f32> for LogProb<f32> {
         LogProb::new(value)
     }
 }
+
+impl<T: Float + std::str::FromStr> std::str::FromStr for LogProb<T> {
+    type Err = ParseLogProbError;
+
+    ///Parses a log-probability from a string, ignoring surrounding whitespace. Any float syntax
+    ///accepted by the standard library works, including scientific notation (`-1e-10`),
+    ///case-insensitive infinities (`-inf`, `-Infinity`) and signed zeros (`+0`, `-0`). Returns a
+    ///[`ParseLogProbError::NotAFloat`] if the string isn't a float and a
+    ///[`ParseLogProbError::NanOrPositive`] if it is positive or NaN.
+    /// ```
+    /// # use logprob::{LogProb, ParseLogProbError};
+    /// assert_eq!("-Inf".parse(), Ok(LogProb::<f64>::impossible()));
+    /// assert_eq!("1e-10".parse::<LogProb<f64>>(), Err(ParseLogProbError::NanOrPositive));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let x: T = s.trim().parse().map_err(|_| ParseLogProbError::NotAFloat)?;
+        Ok(LogProb::new(x)?)
+    }
+}
@@ -0,0 +1,62 @@
+use std::iter::Sum;
+
+use super::{adding::Ln2, Float, LogProb};
+
+///Computes the gradient of the cross-entropy loss with respect to the logits, `softmax(logits) -
+///one_hot(target)`, from the already normalized output of the softmax, `probs`.
+///
+///# Panics
+///Panics if `target` is out of bounds.
+/// ```
+/// # use logprob::{LogProb, cross_entropy_logit_grad};
+/// let probs = [0.25, 0.75].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(cross_entropy_logit_grad(&probs, 1), vec![0.25, -0.25]);
+/// ```
+pub fn cross_entropy_logit_grad<T: Float + Ln2 + Sum>(
+    probs: &[LogProb<T>],
+    target: usize,
+) -> Vec<T> {
+    assert!(target < probs.len(), "target is out of bounds");
+    probs
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if i == target {
+                p.raw_prob() - T::one()
+            } else {
+                p.raw_prob()
+            }
+        })
+        .collect()
+}
+
+///Computes the label-smoothed negative log-likelihood of `target`,
+///`(1 - smoothing) * -probs[target] + smoothing * mean(-probs)`. With a `smoothing` of 0.0, this
+///is the plain negative log-likelihood. A term with a weight of zero is skipped so that
+///impossible elements don't produce NaN.
+///
+///# Panics
+///Panics if `target` is out of bounds or if `smoothing` is not in `[0, 1]`.
+/// ```
+/// # use logprob::{LogProb, label_smoothed_nll};
+/// let probs = [0.5, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// approx::assert_relative_eq!(label_smoothed_nll(&probs, 0, 0.1), 2.0_f64.ln());
+/// ```
+pub fn label_smoothed_nll<T: Float + Ln2>(probs: &[LogProb<T>], target: usize, smoothing: T) -> T {
+    assert!(target < probs.len(), "target is out of bounds");
+    assert!(
+        smoothing >= T::zero() && smoothing <= T::one(),
+        "smoothing must be in [0, 1]"
+    );
+    let nll = if smoothing == T::one() {
+        T::zero()
+    } else {
+        (T::one() - smoothing) * -probs[target].0
+    };
+    if smoothing == T::zero() {
+        return nll;
+    }
+    let n = T::from(probs.len()).unwrap();
+    let mean = probs.iter().fold(T::zero(), |acc, p| acc - p.0) / n;
+    nll + smoothing * mean
+}
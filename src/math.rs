@@ -1,5 +1,8 @@
-use super::LogProb;
-use std::ops::{Add, AddAssign, Mul};
+use super::{Float, LogProb};
+use std::{
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Mul},
+};
 
 impl<T: Add> Add for LogProb<T> {
     type Output = LogProb<T::Output>;
@@ -61,6 +64,40 @@ impl<'a, T: AddAssign<&'a T>> AddAssign<&'a Self> for LogProb<T> {
     }
 }
 
+///Adds up the underlying floats like [`Add`], i.e. multiplies the raw probabilities, so the
+///empty sum is certain.
+impl<T: Float> Sum for LogProb<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(LogProb(T::zero()), |acc, x| acc + x)
+    }
+}
+
+impl<'a, T: Float> Sum<&'a LogProb<T>> for LogProb<T> {
+    fn sum<I: Iterator<Item = &'a LogProb<T>>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+///Multiplies the raw probabilities, which is the same as the [`Sum`] of the log-probabilities, so
+///the empty product is certain.
+/// ```
+/// # use logprob::LogProb;
+/// let x = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+/// let p: LogProb<f64> = x.iter().product();
+/// assert_eq!(p, LogProb::from_raw_prob(0.25).unwrap());
+/// ```
+impl<T: Float> Product for LogProb<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.sum()
+    }
+}
+
+impl<'a, T: Float> Product<&'a LogProb<T>> for LogProb<T> {
+    fn product<I: Iterator<Item = &'a LogProb<T>>>(iter: I) -> Self {
+        iter.sum()
+    }
+}
+
 macro_rules! impl_mul {
     ($unsigned: ty, $float: ty) => {
         impl Mul<LogProb<$float>> for $unsigned {
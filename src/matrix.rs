@@ -0,0 +1,97 @@
+use std::ops::Index;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, Ln2},
+    DimensionMismatch, Float, LogProb,
+};
+
+///A dense row-major matrix of [`LogProb`], e.g. a transition matrix where each row is a
+///distribution.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LogProbMatrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<LogProb<T>>,
+}
+
+impl<T: Float> LogProbMatrix<T> {
+    ///Construct a new `rows` by `cols` [`LogProbMatrix`] from row-major data. Returns a
+    ///[`DimensionMismatch`] if `data` does not have `rows * cols` entries.
+    pub fn new(rows: usize, cols: usize, data: Vec<LogProb<T>>) -> Result<Self, DimensionMismatch> {
+        if rows.checked_mul(cols) != Some(data.len()) {
+            return Err(DimensionMismatch);
+        }
+        Ok(LogProbMatrix { rows, cols, data })
+    }
+
+    ///The number of rows.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    ///The number of columns.
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    ///Gets the `i`th row as a slice.
+    ///
+    ///# Panics
+    ///Panics if `i` is out of bounds.
+    #[inline]
+    pub fn row(&self, i: usize) -> &[LogProb<T>] {
+        assert!(i < self.rows, "row index out of bounds");
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    ///Gets the underlying row-major data.
+    #[inline]
+    pub fn as_slice(&self) -> &[LogProb<T>] {
+        &self.data
+    }
+}
+
+impl<T> Index<(usize, usize)> for LogProbMatrix<T> {
+    type Output = LogProb<T>;
+
+    #[inline]
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+///Multiplies two [`LogProbMatrix`] in log space, so that each entry is the logsumexp of `a_ik +
+///b_kj` over `k`. Entries are clamped at 0.0 so that rounding can't produce an invalid
+///[`LogProb`]. Returns a [`DimensionMismatch`] if `a.cols() != b.rows()`.
+/// ```
+/// # use logprob::{LogProb, LogProbMatrix, log_matmul};
+/// let half = LogProb::from_raw_prob(0.5_f64).unwrap();
+/// let a = LogProbMatrix::new(2, 2, vec![half; 4]).unwrap();
+/// let b = log_matmul(&a, &a).unwrap();
+/// assert_eq!(b[(0, 0)], half);
+/// ```
+pub fn log_matmul<T: Float + Ln2>(
+    a: &LogProbMatrix<T>,
+    b: &LogProbMatrix<T>,
+) -> Result<LogProbMatrix<T>, DimensionMismatch> {
+    if a.cols != b.rows {
+        return Err(DimensionMismatch);
+    }
+    let mut terms = Vec::with_capacity(a.cols);
+    let mut data = Vec::with_capacity(a.rows * b.cols);
+    for i in 0..a.rows {
+        for j in 0..b.cols {
+            terms.clear();
+            terms.extend((0..a.cols).map(|k| a[(i, k)].0 + b[(k, j)].0));
+            data.push(clamp_log_prob(log_sum_exp_float_slice(&terms)));
+        }
+    }
+    Ok(LogProbMatrix {
+        rows: a.rows,
+        cols: b.cols,
+        data,
+    })
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+
+use super::{Float, LogProb};
+
+///A wrapper around [`LogProb`] with the ordering reversed, so that the least probable element is
+///the greatest.
+///
+///Since larger [`LogProb`] are more probable, a [`BinaryHeap`](std::collections::BinaryHeap) of
+///[`LogProb`] is a max-heap which pops the *most* probable element first. Wrapping the elements
+///in [`MinProb`] (or equivalently [`std::cmp::Reverse`]) gives a min-heap which pops the *least*
+///probable element first.
+/// ```
+/// # use logprob::{LogProb, MinProb};
+/// use std::collections::BinaryHeap;
+/// let mut heap: BinaryHeap<MinProb<f64>> = [0.5, 0.1, 0.4]
+///     .map(|x| MinProb(LogProb::from_raw_prob(x).unwrap()))
+///     .into_iter()
+///     .collect();
+/// assert_eq!(heap.pop().unwrap().0, LogProb::from_raw_prob(0.1).unwrap());
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct MinProb<T>(pub LogProb<T>);
+
+impl<T: Float> Eq for MinProb<T> {}
+
+impl<T: Float> PartialOrd for MinProb<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Float> Ord for MinProb<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+impl<T> From<LogProb<T>> for MinProb<T> {
+    #[inline]
+    fn from(value: LogProb<T>) -> Self {
+        MinProb(value)
+    }
+}
+
+///A wrapper around [`LogProb`] which compares near-equal values as equal, to give deterministic
+///groupings of near-tied values when sorting.
+///
+///Comparing values as equal whenever they are within a tolerance isn't transitive, so it can't
+///be used for [`Ord`]. Instead, the log-probabilities are split into buckets of width `tol`
+///(i.e. `(-tol, 0]`, `(-2 tol, -tol]`, and so on), and values in the same bucket compare equal
+///while others are ordered as usual. Values within `tol` of each other can therefore still fall
+///into neighbouring buckets. Buckets are only consistent for a single tolerance, so all values
+///which are compared with each other must share the same `tol`.
+///
+///# Panics
+///Comparisons panic if the two values have different tolerances.
+/// ```
+/// # use logprob::{LogProb, LogProbApprox};
+/// let mut x = [-1.02, -2.5, -1.01, -1.05].map(|x| LogProbApprox::new(LogProb::new(x).unwrap(), 0.1));
+/// x.sort();
+/// let x = x.map(|x| x.value().into_inner());
+/// assert_eq!(x, [-2.5, -1.02, -1.01, -1.05]);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct LogProbApprox<T> {
+    value: LogProb<T>,
+    tol: T,
+}
+
+impl<T: Float> LogProbApprox<T> {
+    ///Wraps `value` so that it is compared with a tolerance of `tol`.
+    ///
+    ///# Panics
+    ///Panics if `tol` is not finite and positive.
+    pub fn new(value: LogProb<T>, tol: T) -> Self {
+        assert!(
+            tol.is_finite() && tol > T::zero(),
+            "tolerance must be finite and positive"
+        );
+        LogProbApprox { value, tol }
+    }
+
+    ///Gets the wrapped [`LogProb`].
+    pub fn value(&self) -> LogProb<T> {
+        self.value
+    }
+
+    ///Gets the tolerance used for comparisons.
+    pub fn tol(&self) -> T {
+        self.tol
+    }
+
+    fn bucket(&self, tol: T) -> T {
+        (self.value.0 / tol).ceil()
+    }
+}
+
+impl<T: Float> PartialEq for LogProbApprox<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Float> Eq for LogProbApprox<T> {}
+
+impl<T: Float> PartialOrd for LogProbApprox<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Float> Ord for LogProbApprox<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        assert!(
+            self.tol == other.tol,
+            "cannot compare values with different tolerances"
+        );
+        self.bucket(self.tol)
+            .partial_cmp(&other.bucket(self.tol))
+            .unwrap()
+    }
+}
@@ -0,0 +1,126 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, Ln2},
+    logaddexp, logsubexp, Float, LogProb,
+};
+
+fn log_cumsum_exp_float<T: Float + Ln2>(vals: &[LogProb<T>]) -> impl Iterator<Item = T> + '_ {
+    vals.iter().scan(T::NEG_INFINITY, |total, x| {
+        *total = logaddexp(*total, x.0);
+        Some(*total)
+    })
+}
+
+///Calculates the cumulative sums of `vals` (as raw probabilities), so that the `i`th element is
+///the logsumexp of `vals[..=i]`. Sums are clamped at 0.0 so that rounding can't produce an
+///invalid [`LogProb`].
+/// ```
+/// # use logprob::{LogProb, log_cumsum_exp};
+/// let x = [0.25, 0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let cumsum: Vec<f64> = log_cumsum_exp(&x).iter().map(|x| x.raw_prob()).collect();
+/// assert_eq!(cumsum, vec![0.25, 0.5, 1.0]);
+/// ```
+pub fn log_cumsum_exp<T: Float + Ln2>(vals: &[LogProb<T>]) -> Vec<LogProb<T>> {
+    log_cumsum_exp_float(vals).map(clamp_log_prob).collect()
+}
+
+///Splits a distribution sorted in descending order into a head carrying a mass of at least `p`
+///(as a raw probability) and a tail carrying the rest, returning the length of the head, i.e.
+///the index at which the cumulative mass first reaches `p`. If `p` is at most 0.0, the head is
+///empty, and if the whole distribution carries less than `p`, the head is everything.
+///
+///The ordering of `sorted` is only checked with a debug assertion.
+///
+///# Panics
+///Panics if `p` is NaN.
+/// ```
+/// # use logprob::{LogProb, split_at_mass};
+/// let x = [0.5, 0.3, 0.15, 0.05].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(split_at_mass(&x, 0.9), 3);
+/// ```
+pub fn split_at_mass<T: Float + Ln2 + Sum>(sorted: &[LogProb<T>], p: T) -> usize {
+    debug_assert!(
+        sorted.windows(2).all(|w| w[0] >= w[1]),
+        "input is not sorted in descending order"
+    );
+    assert!(!p.is_nan(), "p must not be NaN");
+    if p <= T::zero() {
+        return 0;
+    }
+    let target = p.ln();
+    log_cumsum_exp_float(sorted)
+        .position(|total| total >= target)
+        .map_or(sorted.len(), |i| i + 1)
+}
+
+///Precomputed prefix and suffix sums (as raw probabilities) of a slice of [`LogProb`], answering
+///the logsumexp of any prefix or contiguous range in constant time. Ranges are computed by
+///subtracting either prefix sums or suffix sums with [`logsubexp`], whichever carries less mass,
+///so a range is only imprecise if it is tiny compared to both the mass before it and the mass
+///after it. This takes twice the memory of the prefix sums alone.
+/// ```
+/// # use logprob::{LogProb, PrefixLogSumExp};
+/// let x = [0.25, 0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let prefix = PrefixLogSumExp::new(&x);
+/// assert_eq!(prefix.prefix(2), LogProb::from_raw_prob(0.5).unwrap());
+/// assert_eq!(prefix.range(1, 3), LogProb::from_raw_prob(0.75).unwrap());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct PrefixLogSumExp<T> {
+    cumsum: Vec<T>,
+    suffix: Vec<T>,
+}
+
+impl<T: Float + Ln2> PrefixLogSumExp<T> {
+    ///Builds the prefix and suffix sums of `vals` in two passes.
+    pub fn new(vals: &[LogProb<T>]) -> Self {
+        let mut cumsum = Vec::with_capacity(vals.len() + 1);
+        cumsum.push(T::NEG_INFINITY);
+        cumsum.extend(log_cumsum_exp_float(vals));
+        let mut suffix = Vec::with_capacity(vals.len() + 1);
+        suffix.push(T::NEG_INFINITY);
+        suffix.extend(vals.iter().rev().scan(T::NEG_INFINITY, |total, x| {
+            *total = logaddexp(*total, x.0);
+            Some(*total)
+        }));
+        suffix.reverse();
+        PrefixLogSumExp { cumsum, suffix }
+    }
+
+    ///The number of elements the prefix sums were built from.
+    pub fn len(&self) -> usize {
+        self.cumsum.len() - 1
+    }
+
+    ///Checks if the prefix sums were built from an empty slice.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///The logsumexp of the first `i` elements, clamped at 0.0. `prefix(0)` is impossible.
+    ///
+    ///# Panics
+    ///Panics if `i > self.len()`.
+    pub fn prefix(&self, i: usize) -> LogProb<T> {
+        clamp_log_prob(self.cumsum[i])
+    }
+
+    ///The logsumexp of the elements in `i..j`, clamped at 0.0. An empty range is impossible.
+    ///
+    ///# Panics
+    ///Panics if `i > j` or `j > self.len()`.
+    pub fn range(&self, i: usize, j: usize) -> LogProb<T> {
+        assert!(i <= j && j <= self.len(), "range out of bounds");
+        if i == j {
+            return LogProb(T::NEG_INFINITY);
+        }
+        // The relative error of the subtraction grows with the mass of the larger sum, so
+        // subtract from whichever side carries less. Both sums are monotone, so this is never NaN.
+        if self.cumsum[j] <= self.suffix[i] {
+            clamp_log_prob(logsubexp(self.cumsum[j], self.cumsum[i]))
+        } else {
+            clamp_log_prob(logsubexp(self.suffix[i], self.suffix[j]))
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use num_traits::float::TotalOrder;
+
+use super::{adding::Ln2, Float, LogProb};
+
+///Returns the indices of `vals` ordered from most to least probable. Values are compared with
+///`total_cmp`, so 0.0 is more probable than -0.0 and a positive NaN (only possible with
+///[`LogProb::new_unchecked`]) is more probable than everything. The sort is stable, so tied
+///elements keep their original relative order.
+/// ```
+/// # use logprob::{LogProb, argsort_desc};
+/// let v = [0.1, 0.6, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(argsort_desc(&v), vec![1, 2, 0]);
+/// ```
+pub fn argsort_desc<T: Float + TotalOrder>(vals: &[LogProb<T>]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..vals.len()).collect();
+    indices.sort_by(|&i, &j| vals[j].0.total_cmp(&vals[i].0));
+    indices
+}
+
+///Binary searches a slice sorted in ascending order for the index of the first element which is
+///strictly more probable than `threshold` (or `sorted.len()` if there is none). Comparisons use
+///the [`Ord`] implementation of [`LogProb`], so 0.0 and -0.0 are treated as equal.
+///
+///The ordering of `sorted` is only checked with a debug assertion.
+/// ```
+/// # use logprob::{LogProb, partition_above};
+/// let v = [0.1, 0.3, 0.6].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(partition_above(&v, LogProb::from_raw_prob(0.2).unwrap()), 1);
+/// assert_eq!(partition_above(&v, v[1]), 2);
+/// ```
+pub fn partition_above<T: Float>(sorted: &[LogProb<T>], threshold: LogProb<T>) -> usize {
+    debug_assert!(
+        sorted.windows(2).all(|w| w[0] <= w[1]),
+        "input is not sorted in ascending order"
+    );
+    sorted.partition_point(|x| *x <= threshold)
+}
+
+///Returns the index and value of the most probable element along with the log-margin to the
+///second most probable element (i.e. the log of their ratio), which is always non-negative.
+///Ties are broken in favour of the first occurrence and give a margin of 0.0. Returns `None` for
+///an empty slice, and a margin of positive infinity if there is only one element.
+/// ```
+/// # use logprob::{LogProb, confidence};
+/// let x = [0.2, 0.5, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let (i, p, margin) = confidence(&x).unwrap();
+/// assert_eq!((i, p), (1, x[1]));
+/// approx::assert_relative_eq!(margin, (0.5_f64 / 0.3).ln());
+/// ```
+pub fn confidence<T: Float + Ln2>(vals: &[LogProb<T>]) -> Option<(usize, LogProb<T>, T)> {
+    let mut top: Option<(usize, LogProb<T>)> = None;
+    let mut second: Option<LogProb<T>> = None;
+    for (i, &x) in vals.iter().enumerate() {
+        match top {
+            Some((_, t)) if x <= t => {
+                if second.is_none_or(|s| x > s) {
+                    second = Some(x);
+                }
+            }
+            _ => {
+                second = top.map(|(_, t)| t);
+                top = Some((i, x));
+            }
+        }
+    }
+    let (i, top) = top?;
+    let margin = match second {
+        None => T::infinity(),
+        Some(second) if second == top => T::ZERO,
+        Some(second) => top.0 - second.0,
+    };
+    Some((i, top, margin))
+}
+
+///Returns a mask marking the `k` most probable elements of `vals` with `true`. This uses a partial
+///selection rather than a full sort. Ties at the boundary are resolved in favour of the lower
+///index. If `k` is at least the length of `vals`, every element is marked.
+/// ```
+/// # use logprob::{LogProb, top_k_mask};
+/// let v = [0.1, 0.6, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(top_k_mask(&v, 2), vec![false, true, true]);
+/// ```
+pub fn top_k_mask<T: Float>(vals: &[LogProb<T>], k: usize) -> Vec<bool> {
+    let mut mask = vec![false; vals.len()];
+    if k >= vals.len() {
+        mask.fill(true);
+        return mask;
+    } else if k == 0 {
+        return mask;
+    }
+    let mut indices: Vec<usize> = (0..vals.len()).collect();
+    indices.select_nth_unstable_by(k - 1, |&i, &j| vals[j].cmp(&vals[i]).then(i.cmp(&j)));
+    for &i in &indices[..k] {
+        mask[i] = true;
+    }
+    mask
+}
@@ -0,0 +1,110 @@
+use std::{cmp::Ordering, collections::BinaryHeap, iter::Sum};
+
+use rand::Rng;
+
+use super::{adding::Ln2, Float, LogProb};
+
+///Draws the index of an element of `vals` after tempering it by `temperature`, using the
+///Gumbel-max trick on `vals_i / temperature`. A temperature of 0.0 returns the most probable
+///element (the first one in case of ties), while higher temperatures flatten the distribution.
+///`vals` needn't be normalized. Impossible elements are never drawn, and `None` is returned if
+///there are no possible elements.
+///
+///# Panics
+///Panics if `temperature` is NaN or negative.
+/// ```
+/// # use logprob::{LogProb, sample_tempered};
+/// let v = [0.1, 0.6, 0.0, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let mut rng = rand::thread_rng();
+/// assert_eq!(sample_tempered(&v, 0.0, &mut rng), Some(1));
+/// assert_ne!(sample_tempered(&v, 1.0, &mut rng), Some(2));
+/// ```
+pub fn sample_tempered<T: Float + Ln2 + Sum, R: Rng + ?Sized>(
+    vals: &[LogProb<T>],
+    temperature: T,
+    rng: &mut R,
+) -> Option<usize> {
+    assert!(temperature >= T::zero(), "temperature must be non-negative");
+    let mut best: Option<(usize, T)> = None;
+    for (i, x) in vals.iter().enumerate() {
+        if x.0 == T::NEG_INFINITY {
+            continue;
+        }
+        let score = if temperature == T::zero() {
+            x.0
+        } else {
+            let u: f64 = rng.gen_range(f64::MIN_POSITIVE..1.0);
+            x.0 / temperature + T::from(-(-u.ln()).ln()).unwrap()
+        };
+        if best.is_none_or(|(_, b)| score > b) {
+            best = Some((i, score));
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+///An item in the reservoir of [`weighted_reservoir_sample`], ordered by its key.
+struct Keyed<T>(T, usize);
+
+impl<T: Float> PartialEq for Keyed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Float> Eq for Keyed<T> {}
+
+impl<T: Float> PartialOrd for Keyed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Float> Ord for Keyed<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+///Samples `k` distinct indices without replacement from a stream of (possibly unnormalized)
+///weights in a single pass, using the A-Res algorithm of Efraimidis and Spirakis. Each item gets
+///the key `u^(1/w)` for a uniform `u`, and the `k` items with the largest keys are kept in a
+///bounded heap. Rather than the key itself, which underflows for small weights, the items are
+///ranked by `ln(-ln(u)) - ln(w)`, which is decreasing in the key. Impossible items are never
+///sampled, so fewer than `k` indices are returned if there are fewer than `k` possible items.
+///The indices are returned in order of their keys, largest first.
+/// ```
+/// # use logprob::{LogProb, weighted_reservoir_sample};
+/// let w = [0.1, 0.0, 0.6, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let sample = weighted_reservoir_sample(w.into_iter(), 2, &mut rand::thread_rng());
+/// assert_eq!(sample.len(), 2);
+/// assert!(!sample.contains(&1));
+/// ```
+pub fn weighted_reservoir_sample<T: Float, R: Rng + ?Sized>(
+    log_weights: impl Iterator<Item = LogProb<T>>,
+    k: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    if k == 0 {
+        return vec![];
+    }
+    let mut reservoir: BinaryHeap<Keyed<T>> = BinaryHeap::with_capacity(k);
+    for (i, w) in log_weights.enumerate() {
+        if w.0 == T::neg_infinity() {
+            continue;
+        }
+        let u: f64 = rng.gen_range(f64::MIN_POSITIVE..1.0);
+        let rank = T::from((-u.ln()).ln()).unwrap() - w.0;
+        if reservoir.len() < k {
+            reservoir.push(Keyed(rank, i));
+        } else if reservoir.peek().is_some_and(|worst| rank < worst.0) {
+            reservoir.pop();
+            reservoir.push(Keyed(rank, i));
+        }
+    }
+    reservoir
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Keyed(_, i)| i)
+        .collect()
+}
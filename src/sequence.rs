@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use super::{Float, LogProb};
+
+///Calculates the joint probability of a sequence of independent events, i.e. the product of
+///their probabilities (or the sum of their log-probabilities). An empty sequence is certain.
+/// ```
+/// # use logprob::{LogProb, joint_log_prob};
+/// let x = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+/// assert_eq!(joint_log_prob(&x), LogProb::from_raw_prob(0.25).unwrap());
+/// ```
+pub fn joint_log_prob<T: Float>(probs: &[LogProb<T>]) -> LogProb<T> {
+    LogProb(probs.iter().fold(T::zero(), |acc, x| acc + x.0))
+}
+
+///Calculates the surprisal, `-lp_i`, of each token of a sequence along with their sum, the total
+///negative log-likelihood of the sequence. The perplexity of the sequence is
+///`exp(total / n)`.
+/// ```
+/// # use logprob::{LogProb, sequence_nll};
+/// let x = [0.5_f64, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let (per_token, total) = sequence_nll(&x);
+/// assert_eq!(per_token, vec![2.0_f64.ln(), 4.0_f64.ln()]);
+/// approx::assert_relative_eq!(total, 8.0_f64.ln());
+/// ```
+pub fn sequence_nll<T: Float>(token_log_probs: &[LogProb<T>]) -> (Vec<T>, T) {
+    let per_token: Vec<T> = token_log_probs.iter().map(|x| -x.0).collect();
+    let total = per_token.iter().fold(T::zero(), |acc, &x| acc + x);
+    (per_token, total)
+}
+
+///Keeps track of the perplexity, `exp(mean surprisal)`, of the last `window` tokens of a stream.
+///The sum of the surprisals in the window is updated as tokens are pushed and evicted, rather
+///than recomputed. Impossible tokens are counted separately, so the perplexity is infinite
+///while one is in the window and recovers once it is evicted.
+/// ```
+/// # use logprob::{LogProb, RollingPerplexity};
+/// let mut ppl = RollingPerplexity::new(2);
+/// for p in [0.1_f64, 0.5, 0.5] {
+///     ppl.push(LogProb::from_raw_prob(p).unwrap());
+/// }
+/// approx::assert_relative_eq!(ppl.perplexity(), 2.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RollingPerplexity<T> {
+    window: usize,
+    surprisals: VecDeque<T>,
+    sum: T,
+    n_impossible: usize,
+}
+
+impl<T: Float> RollingPerplexity<T> {
+    ///Creates a new [`RollingPerplexity`] over the last `window` tokens.
+    ///
+    ///# Panics
+    ///Panics if `window` is 0.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window must be at least 1");
+        RollingPerplexity {
+            window,
+            surprisals: VecDeque::with_capacity(window),
+            sum: T::zero(),
+            n_impossible: 0,
+        }
+    }
+
+    ///Adds the log-probability of the next token, evicting the oldest token if the window is
+    ///full.
+    pub fn push(&mut self, lp: LogProb<T>) {
+        if self.surprisals.len() == self.window {
+            let evicted = self.surprisals.pop_front().unwrap();
+            if evicted.is_infinite() {
+                self.n_impossible -= 1;
+            } else {
+                self.sum = self.sum - evicted;
+            }
+        }
+        let surprisal = -lp.0;
+        if surprisal.is_infinite() {
+            self.n_impossible += 1;
+        } else {
+            self.sum = self.sum + surprisal;
+        }
+        self.surprisals.push_back(surprisal);
+    }
+
+    ///Gets the perplexity of the tokens currently in the window, which may not be full yet.
+    ///Returns NaN if no token has been pushed.
+    pub fn perplexity(&self) -> T {
+        if self.surprisals.is_empty() {
+            T::nan()
+        } else if self.n_impossible > 0 {
+            T::infinity()
+        } else {
+            (self.sum / T::from(self.surprisals.len()).unwrap()).exp()
+        }
+    }
+}
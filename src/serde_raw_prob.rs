@@ -0,0 +1,39 @@
+//! A serde adapter which stores a [`LogProb`] as its raw probability rather than its log value.
+//! This is useful for human-facing output where `0.25` is easier to read than `-1.386...`.
+//!
+//! This loses precision, and log-probabilities below the smallest raw probability of the float
+//! type (about -745 for `f64` and -103 for `f32`) are serialized as 0.0, so they come back as
+//! [`LogProb::impossible`]. Use the default [`LogProb`] serialization if that matters.
+//!
+//! ```
+//! use logprob::LogProb;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Prediction {
+//!     #[serde(with = "logprob::serde_raw_prob")]
+//!     prob: LogProb<f64>,
+//! }
+//!
+//! let x = Prediction { prob: LogProb::from_raw_prob(0.25).unwrap() };
+//! assert_eq!(serde_json::to_string(&x).unwrap(), r#"{"prob":0.25}"#);
+//! ```
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Float, LogProb};
+
+///Serializes a [`LogProb`] as its raw probability.
+pub fn serialize<T: Float + Serialize, S: Serializer>(
+    val: &LogProb<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    val.raw_prob().serialize(serializer)
+}
+
+///Deserializes a [`LogProb`] from a raw probability, returning an error if it is not in [0, 1].
+pub fn deserialize<'de, T: Float + Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<LogProb<T>, D::Error> {
+    LogProb::from_raw_prob(T::deserialize(deserializer)?).map_err(D::Error::custom)
+}
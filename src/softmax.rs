@@ -1,8 +1,16 @@
 use std::iter::Sum;
 
-use super::{adding::Ln2, Float, FloatIsNanOrPositiveInfinity, LogProb};
+use num_traits::float::TotalOrder;
 
-///Returns an iterator with the softmax values of a slice of floats.
+use super::{adding::Ln2, Float, FloatIsNanOrPositiveInfinity, LengthMismatch, LogProb};
+
+///Returns an iterator with the softmax values of a slice of floats. If every value is negative
+///infinity, the result is uniform, as it is for any other slice of equal values.
+/// ```
+/// # use logprob::{LogProb, softmax};
+/// let x: Vec<_> = softmax(&[f64::NEG_INFINITY; 2]).unwrap().collect();
+/// assert_eq!(x, vec![LogProb::from_raw_prob(0.5).unwrap(); 2]);
+/// ```
 pub fn softmax<T: Float + Sum<T> + Ln2>(
     val: &[T],
 ) -> Result<impl Iterator<Item = LogProb<T>>, FloatIsNanOrPositiveInfinity> {
@@ -20,12 +28,101 @@ pub fn softmax<T: Float + Sum<T> + Ln2>(
         .iter()
         .max_by(|x, y| x.partial_cmp(y).unwrap())
         .unwrap_or(&T::ZERO);
+    let (v, max) = if max == T::NEG_INFINITY {
+        (vec![T::ZERO; v.len()], T::ZERO)
+    } else {
+        (v, max)
+    };
     let s: T = v.iter().map(|&x| x - max).map(|x| x.exp()).sum::<T>().ln();
     Ok(v.into_iter()
         .map(move |x| x - s - max)
         .map(|x| LogProb::new(x).unwrap()))
 }
 
+///Turns a slice of logits into a normalized distribution of [`LogProb`]. This is the same as
+///[`softmax`], but collects the result into a vector, so logits which are all negative infinity
+///likewise give a uniform distribution.
+/// ```
+/// # use logprob::{LogProb, from_logits};
+/// let x = from_logits(&[0.0_f64, 0.0]).unwrap();
+/// assert_eq!(x, vec![LogProb::from_raw_prob(0.5).unwrap(); 2]);
+/// assert_eq!(from_logits(&[f64::NEG_INFINITY; 2]).unwrap(), x);
+/// ```
+pub fn from_logits<T: Float + Sum + Ln2>(
+    logits: &[T],
+) -> Result<Vec<LogProb<T>>, FloatIsNanOrPositiveInfinity> {
+    Ok(softmax(logits)?.collect())
+}
+
+///Returns the index of the largest logit, which is also the most probable element of the
+///[`softmax`], without computing the softmax. Logits are compared with `total_cmp`, so 0.0 is
+///larger than -0.0 and positive NaN is larger than everything. Ties are broken in favour of the
+///first occurrence and `None` is returned for an empty slice.
+/// ```
+/// # use logprob::argmax_logit;
+/// assert_eq!(argmax_logit(&[0.5_f64, 2.0, -1.0, 2.0]), Some(1));
+/// assert_eq!(argmax_logit::<f32>(&[]), None);
+/// ```
+pub fn argmax_logit<T: Float + TotalOrder>(logits: &[T]) -> Option<usize> {
+    logits
+        .iter()
+        .enumerate()
+        .fold(None, |best: Option<(usize, T)>, (i, &x)| match best {
+            Some((_, b)) if x.total_cmp(&b) != std::cmp::Ordering::Greater => best,
+            _ => Some((i, x)),
+        })
+        .map(|(i, _)| i)
+}
+
+///Computes the product of the Jacobian of the softmax with `upstream`, i.e. the gradient with
+///respect to the logits given the gradient `upstream` with respect to the probabilities, from the
+///already normalized output of the softmax, `probs`. Since the Jacobian is symmetric, the `i`th
+///element is `p_i * (v_i - Σ p_j v_j)`. Returns a [`LengthMismatch`] if the slices have
+///different lengths.
+/// ```
+/// # use logprob::{LogProb, softmax_jvp};
+/// let probs = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+/// assert_eq!(softmax_jvp(&probs, &[1.0, 0.0]).unwrap(), vec![0.25, -0.25]);
+/// ```
+pub fn softmax_jvp<T: Float + Ln2 + Sum>(
+    probs: &[LogProb<T>],
+    upstream: &[T],
+) -> Result<Vec<T>, LengthMismatch> {
+    if probs.len() != upstream.len() {
+        return Err(LengthMismatch);
+    }
+    let p: Vec<T> = probs.iter().map(LogProb::raw_prob).collect();
+    let dot: T = p.iter().zip(upstream).map(|(&p, &v)| p * v).sum();
+    Ok(p.iter()
+        .zip(upstream)
+        .map(|(&p, &v)| p * (v - dot))
+        .collect())
+}
+
+///Returns an iterator with the softmax values of a slice of floats, where every input is first
+///clipped to be at most `max_logit`. This stops a single enormous logit from swamping the whole
+///distribution. Positive infinity is clipped like any other value, but NaN is still an error, as
+///is a `max_logit` which is NaN or negative infinity.
+/// ```
+/// # use logprob::{LogProb, softmax_clamped};
+/// let x: Vec<_> = softmax_clamped(&[0.0, f64::INFINITY], 0.0).unwrap().collect();
+/// assert_eq!(x, vec![LogProb::from_raw_prob(0.5).unwrap(); 2]);
+/// assert!(softmax_clamped(&[0.0_f64], f64::NEG_INFINITY).is_err());
+/// ```
+pub fn softmax_clamped<T: Float + Sum<T> + Ln2>(
+    val: &[T],
+    max_logit: T,
+) -> Result<impl Iterator<Item = LogProb<T>>, FloatIsNanOrPositiveInfinity> {
+    if max_logit.is_nan() || max_logit == T::NEG_INFINITY {
+        return Err(FloatIsNanOrPositiveInfinity);
+    }
+    let v: Vec<_> = val
+        .iter()
+        .map(|x| if x.is_nan() { *x } else { x.min(max_logit) })
+        .collect();
+    softmax(&v)
+}
+
 ///This trait allows iterators to have [`softmax`].
 pub trait Softmax: Iterator {
     ///Gets the softmax from an iterator as another iterator
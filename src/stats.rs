@@ -0,0 +1,213 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, log_sum_exp_float_slice, Ln2},
+    as_float_slice, Float, LengthMismatch, LogProb,
+};
+
+///Counts how many log-probabilities fall in each of `n_bins` buckets of equal width (in log
+///space) between `lo` and `hi`. Values outside of the range are counted in the first or last
+///bucket, so impossible values are always in the first bucket. Each bucket includes its lower
+///bound and excludes its upper bound, except for the last which includes `hi`.
+///
+///# Panics
+///Panics if `lo` is impossible or if `lo >= hi`.
+/// ```
+/// # use logprob::{LogProb, prob_histogram};
+/// let x = [-0.5, -1.5, -1.7, -3.0].map(|x| LogProb::new(x).unwrap());
+/// let lo = LogProb::new(-2.0).unwrap();
+/// let hi = LogProb::new(0.0).unwrap();
+/// assert_eq!(prob_histogram(&x, 2, lo, hi), vec![3, 1]);
+/// ```
+pub fn prob_histogram<T: Float>(
+    vals: &[LogProb<T>],
+    n_bins: usize,
+    lo: LogProb<T>,
+    hi: LogProb<T>,
+) -> Vec<usize> {
+    assert!(
+        lo.0 != T::neg_infinity() && lo < hi,
+        "histogram range must be finite and non-empty"
+    );
+    let mut counts = vec![0; n_bins];
+    if n_bins == 0 {
+        return counts;
+    }
+    let n = T::from(n_bins).unwrap();
+    let width = hi.0 - lo.0;
+    for x in vals {
+        let bin = ((x.0 - lo.0) / width * n).floor();
+        let bin = if bin < T::zero() {
+            0
+        } else {
+            bin.to_usize().unwrap_or(usize::MAX).min(n_bins - 1)
+        };
+        counts[bin] += 1;
+    }
+    counts
+}
+
+///Accumulates the running mean of the inner values of a stream of [`LogProb`] (e.g. the average
+///log-likelihood) with Welford's update, which avoids the precision loss of keeping a large
+///running sum. Impossible values are not included in the mean, since a single one would make it
+///negative infinity forever; instead they are counted separately (see
+///[`OnlineMean::n_impossible`]).
+/// ```
+/// # use logprob::{LogProb, OnlineMean};
+/// let mut mean = OnlineMean::new();
+/// mean.push(LogProb::new(-1.0_f64).unwrap());
+/// mean.push(LogProb::new(-3.0).unwrap());
+/// mean.push(LogProb::impossible());
+/// assert_eq!(mean.mean(), -2.0);
+/// assert_eq!(mean.count(), 2);
+/// assert_eq!(mean.n_impossible(), 1);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct OnlineMean<T> {
+    mean: T,
+    count: usize,
+    n_impossible: usize,
+}
+
+impl<T: Float> OnlineMean<T> {
+    ///Creates a new accumulator which hasn't seen any values.
+    pub fn new() -> Self {
+        OnlineMean {
+            mean: T::zero(),
+            count: 0,
+            n_impossible: 0,
+        }
+    }
+
+    ///Adds `x` to the running mean, or counts it as impossible if it is negative infinity.
+    pub fn push(&mut self, x: LogProb<T>) {
+        if x.0 == T::neg_infinity() {
+            self.n_impossible += 1;
+            return;
+        }
+        self.count += 1;
+        self.mean = self.mean + (x.0 - self.mean) / T::from(self.count).unwrap();
+    }
+
+    ///Returns the mean of the possible values so far, or NaN if there are none.
+    pub fn mean(&self) -> T {
+        if self.count == 0 {
+            T::nan()
+        } else {
+            self.mean
+        }
+    }
+
+    ///The number of values included in the mean.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    ///The number of impossible values which were pushed and left out of the mean.
+    pub fn n_impossible(&self) -> usize {
+        self.n_impossible
+    }
+}
+
+impl<T: Float> Default for OnlineMean<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Adds up (as raw probabilities) the elements of `vals` whose log-probability lies in `[lo, hi]`,
+///e.g. to find the mass in the tail of a distribution. The result is clamped at 0.0.
+/// ```
+/// # use logprob::{LogProb, mass_in_range};
+/// let x = [0.1, 0.2, 0.3, 0.4].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// let lo = LogProb::from_raw_prob(0.15).unwrap();
+/// let hi = LogProb::from_raw_prob(0.35).unwrap();
+/// approx::assert_relative_eq!(mass_in_range(&x, lo, hi).raw_prob(), 0.5);
+/// ```
+pub fn mass_in_range<T: Float + Ln2 + Sum>(
+    vals: &[LogProb<T>],
+    lo: LogProb<T>,
+    hi: LogProb<T>,
+) -> LogProb<T> {
+    let in_range = |x: &&LogProb<T>| lo <= **x && **x <= hi;
+    let max = vals
+        .iter()
+        .filter(in_range)
+        .fold(T::NEG_INFINITY, |acc, x| acc.max(x.0));
+    if max == T::NEG_INFINITY {
+        return LogProb(T::NEG_INFINITY);
+    }
+    let total = vals
+        .iter()
+        .filter(in_range)
+        .map(|x| (x.0 - max).exp())
+        .sum::<T>();
+    clamp_log_prob(total.ln() + max)
+}
+
+///Finds the weighted median of `values`, i.e. the smallest value at which the cumulative mass of
+///`weights` (taken in order of the values) first reaches half of the total mass. This is
+///[`weighted_quantiles`] with a single quantile of 0.5. Returns a [`LengthMismatch`] if the
+///slices have different lengths.
+///
+///# Panics
+///Panics if no weight is possible or if two values can't be compared (e.g. a NaN value).
+/// ```
+/// # use logprob::{LogProb, weighted_median};
+/// let weights = [0.1, 0.2, 0.6, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+/// assert_eq!(weighted_median(&[4, 1, 3, 2], &weights), Ok(3));
+/// ```
+pub fn weighted_median<T: Float + Ln2, V: PartialOrd + Copy>(
+    values: &[V],
+    weights: &[LogProb<T>],
+) -> Result<V, LengthMismatch> {
+    Ok(weighted_quantiles(values, weights, &[T::from(0.5).unwrap()])?[0])
+}
+
+///Finds several weighted quantiles of `values` at once, sorting them only once. The quantile `q`
+///is the smallest value at which the cumulative mass of `weights` (taken in order of the values)
+///first reaches `q` times the total mass. The weights needn't be normalized, and values with
+///impossible weights are skipped. Returns a [`LengthMismatch`] if `values` and `weights` have
+///different lengths.
+///
+///# Panics
+///Panics if no weight is possible, if a quantile isn't in `[0, 1]`, or if two values can't be
+///compared (e.g. a NaN value).
+/// ```
+/// # use logprob::{LogProb, weighted_quantiles};
+/// let weights = [LogProb::from_raw_prob(0.25_f64).unwrap(); 4];
+/// let qs = weighted_quantiles(&[10, 40, 20, 30], &weights, &[0.25, 0.5, 0.75]).unwrap();
+/// assert_eq!(qs, vec![10, 20, 30]);
+/// ```
+pub fn weighted_quantiles<T: Float + Ln2, V: PartialOrd + Copy>(
+    values: &[V],
+    weights: &[LogProb<T>],
+    qs: &[T],
+) -> Result<Vec<V>, LengthMismatch> {
+    if values.len() != weights.len() {
+        return Err(LengthMismatch);
+    }
+    assert!(
+        qs.iter().all(|&q| q >= T::zero() && q <= T::one()),
+        "quantiles must be in [0, 1]"
+    );
+    let total = log_sum_exp_float_slice(as_float_slice(weights));
+    assert!(total != T::NEG_INFINITY, "no weight is possible");
+    let mut order: Vec<usize> = (0..values.len())
+        .filter(|&i| weights[i].0 != T::NEG_INFINITY)
+        .collect();
+    order.sort_by(|&i, &j| values[i].partial_cmp(&values[j]).unwrap());
+    let cumulative: Vec<T> = order
+        .iter()
+        .scan(T::zero(), |acc, &i| {
+            *acc = *acc + (weights[i].0 - total).exp();
+            Some(*acc)
+        })
+        .collect();
+    // Rounding can leave the cumulative mass just short of a quantile at the end.
+    let last = order.len() - 1;
+    Ok(qs
+        .iter()
+        .map(|&q| values[order[cumulative.partition_point(|&c| c < q).min(last)]])
+        .collect())
+}
@@ -0,0 +1,54 @@
+use std::iter::Sum;
+
+use super::{
+    adding::{clamp_log_prob, Ln2},
+    Float, LogProb,
+};
+
+///Yields the indices of the support of `vals`, i.e. the indices of events which are possible
+///(whose log-probability is not negative infinity).
+/// ```
+/// # use logprob::{LogProb, support_indices};
+/// let x = [-1.0, f64::NEG_INFINITY, -0.5].map(|x| LogProb::new(x).unwrap());
+/// assert_eq!(support_indices(&x).collect::<Vec<_>>(), vec![0, 2]);
+/// ```
+pub fn support_indices<T: Float>(vals: &[LogProb<T>]) -> impl Iterator<Item = usize> + '_ {
+    vals.iter()
+        .enumerate()
+        .filter(|(_, x)| x.0 != T::neg_infinity())
+        .map(|(i, _)| i)
+}
+
+///Counts the number of possible events in `vals` (whose log-probability is not negative
+///infinity).
+pub fn support_size<T: Float>(vals: &[LogProb<T>]) -> usize {
+    vals.iter().filter(|x| x.0 != T::neg_infinity()).count()
+}
+
+///Normalizes `vals` in place so that they sum to one (as raw probabilities), only touching the
+///support. The logsumexp is taken over the possible events and subtracted from them, while
+///impossible events are left as they are. If every event is impossible, nothing is changed.
+/// ```
+/// # use logprob::{LogProb, renormalize_support};
+/// let mut x = [-1.0, f64::NEG_INFINITY, -1.0].map(|x| LogProb::new(x).unwrap());
+/// renormalize_support(&mut x);
+/// assert_eq!(x[0], LogProb::from_raw_prob(0.5).unwrap());
+/// assert_eq!(x[1], LogProb::impossible());
+/// ```
+pub fn renormalize_support<T: Float + Ln2 + Sum>(vals: &mut [LogProb<T>]) {
+    let possible = |x: &&mut LogProb<T>| x.0 != T::NEG_INFINITY;
+    let max = vals.iter().fold(T::NEG_INFINITY, |acc, x| acc.max(x.0));
+    if max == T::NEG_INFINITY {
+        return;
+    }
+    let total = vals
+        .iter_mut()
+        .filter(possible)
+        .map(|x| (x.0 - max).exp())
+        .sum::<T>()
+        .ln()
+        + max;
+    vals.iter_mut()
+        .filter(possible)
+        .for_each(|x| *x = clamp_log_prob(x.0 - total));
+}
@@ -1,6 +1,23 @@
 use anyhow::Result;
 use logprob::{
-    log_sum_exp, log_sum_exp_clamped, log_sum_exp_float, softmax, LogProb, LogSumExp, Softmax,
+    argmax_logit, argsort_desc, as_float_slice, bayesian_update, bhattacharyya, cauchy_log_pdf,
+    conditional, confidence, cross_entropy_logit_grad, entropy_rate, format_distribution,
+    from_float_slice, from_logits, from_raw_probs, from_raw_probs_f64_to_f32,
+    from_raw_probs_floored, generalized_entropy, gini_impurity, hellinger_distance, hmm_forward,
+    joint_log_prob, kl_divergence, label_smoothed_nll, log_convolve, log_cumsum_exp, log_matmul,
+    log_prob_linspace, log_ratios, log_sum_exp, log_sum_exp_checked, log_sum_exp_clamped,
+    log_sum_exp_f32, log_sum_exp_f64, log_sum_exp_float, log_sum_exp_fn, log_sum_exp_groups,
+    log_sum_exp_raw, logaddexp, logsubexp, marginals, mass_in_range, mutual_information,
+    narrow_to_f32, normal_log_pdf, opposite_probs_into, partition_above, prob_histogram,
+    renormalize_support, renyi_divergence, sequence_nll, softmax, softmax_clamped, softmax_jvp,
+    split_at_mass, support_indices, support_size, temper, to_proper_distribution, top_k_mask,
+    total_variation_distance, viterbi, weighted_median, weighted_quantiles, BeliefState,
+    Categorical, CategoricalError, ChunkedLogSumExp, ConditioningError, DimensionMismatch,
+    EntropyKind, FloatIsNan, FloatIsNanOrPositive, FloatIsNanOrPositiveInfinity, FloorError,
+    InvalidTemperature, LengthMismatch, LogDensity, LogProb, LogProbApprox, LogProbIterator,
+    LogProbMatrix, LogSumExp, LogSumExpBuilder, LogSumExpError, LogSumExpResult, MinProb,
+    OnlineMean, ParseLogProbError, PrefixLogSumExp, ProbabilitiesSumToGreaterThanOne,
+    RollingPerplexity, Softmax,
 };
 
 #[test]
@@ -68,6 +85,7 @@ fn multiplication() -> Result<()> {
     let x: LogProb<f64> = 4_u8 * LogProb::new(-3.0)?;
     assert_eq!(x, LogProb::new(-12.0)?);
 
+    #[allow(clippy::erasing_op)]
     let x: LogProb<f64> = 0_u8 * LogProb::new(-3.0)?;
     assert_eq!(x, LogProb::new(0.0)?);
 
@@ -248,3 +266,2510 @@ fn softmax_test() -> Result<()> {
     assert!(softmax(&x).is_err());
     Ok(())
 }
+
+#[test]
+fn matrix_multiplication() -> Result<()> {
+    let a = LogProbMatrix::new(
+        2,
+        2,
+        [0.9, 0.1, 0.4, 0.6]
+            .map(|x| LogProb::from_raw_prob(x).unwrap())
+            .to_vec(),
+    )?;
+    let b = LogProbMatrix::new(
+        2,
+        2,
+        [0.3, 0.7, 0.2, 0.8]
+            .map(|x| LogProb::from_raw_prob(x).unwrap())
+            .to_vec(),
+    )?;
+    let c = log_matmul(&a, &b)?;
+    assert_eq!((c.rows(), c.cols()), (2, 2));
+    for i in 0..c.rows() {
+        approx::assert_relative_eq!(log_sum_exp_float(c.row(i)), 0.0, epsilon = 1e-12);
+    }
+    approx::assert_relative_eq!(c[(0, 0)].raw_prob(), 0.9 * 0.3 + 0.1 * 0.2);
+    approx::assert_relative_eq!(c[(1, 1)].raw_prob(), 0.4 * 0.7 + 0.6 * 0.8);
+
+    let d = LogProbMatrix::new(
+        1,
+        2,
+        vec![LogProb::new(0.0)?, LogProb::new(f64::NEG_INFINITY)?],
+    )?;
+    assert!(log_matmul(&a, &d).is_err());
+    assert!(LogProbMatrix::new(2, 2, vec![LogProb::new(0.0_f64)?]).is_err());
+    Ok(())
+}
+
+#[test]
+fn hmm_forward_test() -> Result<()> {
+    let p = |x: f64| LogProb::from_raw_prob(x).unwrap();
+    let initial = [p(0.6), p(0.4)];
+    let a = [[0.7, 0.3], [0.4, 0.6]];
+    let transitions = LogProbMatrix::new(2, 2, a.concat().into_iter().map(p).collect())?;
+    let b = [[0.5, 0.4, 0.1], [0.1, 0.3, 0.6]];
+    let observations = [0, 1, 2, 2];
+    let emissions: Vec<_> = observations
+        .iter()
+        .map(|&o| vec![p(b[0][o]), p(b[1][o])])
+        .collect();
+
+    let mut brute_force = 0.0;
+    for path in 0..(1 << observations.len()) {
+        let state = |t: usize| (path >> t) & 1;
+        let mut prob = [0.6, 0.4][state(0)] * b[state(0)][observations[0]];
+        for t in 1..observations.len() {
+            prob *= a[state(t - 1)][state(t)] * b[state(t)][observations[t]];
+        }
+        brute_force += prob;
+    }
+    let likelihood = hmm_forward(&initial, &transitions, &emissions)?;
+    approx::assert_relative_eq!(likelihood.raw_prob(), brute_force, epsilon = 1e-12);
+
+    let emissions = vec![
+        vec![p(0.5), p(0.0)],
+        vec![p(0.0), p(0.3)],
+        vec![p(0.0), p(0.0)],
+    ];
+    let likelihood = hmm_forward(&initial, &transitions, &emissions)?;
+    assert_eq!(likelihood, LogProb::new(f64::NEG_INFINITY)?);
+    let likelihood = hmm_forward(&initial, &transitions, &emissions[..2])?;
+    approx::assert_relative_eq!(likelihood.raw_prob(), 0.6 * 0.5 * 0.3 * 0.3);
+
+    assert!(hmm_forward(&initial, &transitions, &[vec![p(0.5)]]).is_err());
+    assert!(hmm_forward(&initial[..1], &transitions, &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn viterbi_test() -> Result<()> {
+    let p = |x: f64| LogProb::from_raw_prob(x).unwrap();
+    let initial = [p(0.6), p(0.4)];
+    let transitions = LogProbMatrix::new(2, 2, [0.7, 0.3, 0.4, 0.6].map(p).to_vec())?;
+    let b = [[0.5, 0.4, 0.1], [0.1, 0.3, 0.6]];
+    let emissions: Vec<_> = [0, 1, 2]
+        .iter()
+        .map(|&o| vec![p(b[0][o]), p(b[1][o])])
+        .collect();
+
+    let (path, prob) = viterbi(&initial, &transitions, &emissions);
+    assert_eq!(path, vec![0, 0, 1]);
+    approx::assert_relative_eq!(
+        prob.raw_prob(),
+        0.6 * 0.5 * 0.7 * 0.4 * 0.3 * 0.6,
+        epsilon = 1e-12
+    );
+
+    let uniform = LogProbMatrix::new(2, 2, vec![p(0.5); 4])?;
+    let (path, prob) = viterbi(&[p(0.5), p(0.5)], &uniform, &vec![vec![p(1.0); 2]; 3]);
+    assert_eq!(path, vec![0, 0, 0]);
+    approx::assert_relative_eq!(prob.raw_prob(), 0.125);
+
+    let (path, prob) = viterbi(&initial, &transitions, &[]);
+    assert!(path.is_empty());
+    assert_eq!(prob, LogProb::new(0.0)?);
+
+    // SAFETY: deliberately invalid, to check that decoding doesn't panic on NaN.
+    let nan = unsafe { LogProb::new_unchecked(f64::NAN) };
+    let mut emissions = emissions;
+    emissions[1][0] = nan;
+    let (path, prob) = viterbi(&initial, &transitions, &emissions);
+    assert_eq!(path.len(), 3);
+    assert!(prob.into_inner().is_nan());
+    Ok(())
+}
+
+#[test]
+fn serde_round_trip() -> Result<()> {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Probs {
+        log: LogProb<f64>,
+        #[serde(with = "logprob::serde_raw_prob")]
+        raw: LogProb<f64>,
+    }
+    let x = Probs {
+        log: LogProb::from_raw_prob(0.5)?,
+        raw: LogProb::from_raw_prob(0.25)?,
+    };
+    let json = serde_json::to_string(&x)?;
+    assert_eq!(json, format!("{{\"log\":{},\"raw\":0.25}}", 0.5_f64.ln()));
+    assert_eq!(serde_json::from_str::<Probs>(&json)?, x);
+
+    // Underflows to a raw probability of 0.0.
+    let x = Probs {
+        log: LogProb::new(-800.0)?,
+        raw: LogProb::new(-800.0)?,
+    };
+    let json = serde_json::to_string(&x)?;
+    assert_eq!(json, r#"{"log":-800.0,"raw":0.0}"#);
+    let y = serde_json::from_str::<Probs>(&json)?;
+    assert_eq!(y.log, x.log);
+    assert_eq!(y.raw, LogProb::impossible());
+
+    assert!(serde_json::from_str::<Probs>(r#"{"log":-1.0,"raw":1.5}"#).is_err());
+    assert!(serde_json::from_str::<Probs>(r#"{"log":-1.0,"raw":-0.5}"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn softmax_clamped_test() -> Result<()> {
+    let x: Vec<f64> = vec![1.0, 0.0, 1000.0];
+    let s: Vec<_> = softmax(&x)?.collect();
+    approx::assert_relative_eq!(s[2].into_inner(), 0.0);
+    let clamped: Vec<_> = softmax_clamped(&x, 2.0)?.collect();
+    let expected: Vec<_> = softmax(&[1.0, 0.0, 2.0])?.collect();
+    assert_eq!(clamped, expected);
+    assert!(clamped[2].raw_prob() < 0.7);
+
+    let x: Vec<f64> = vec![0.5, f64::INFINITY, 5.];
+    let clamped: Vec<_> = softmax_clamped(&x, 5.0)?.collect();
+    assert_eq!(clamped[1], clamped[2]);
+    assert!(softmax_clamped(&[0.5, f64::NAN], 1.0).is_err());
+    assert!(softmax_clamped(&[0.5, 1.0], f64::NAN).is_err());
+    assert!(softmax_clamped(&[0.5, 1.0], f64::NEG_INFINITY).is_err());
+    let all_impossible: Vec<_> = softmax_clamped(&[f64::NEG_INFINITY; 4], 1.0)?.collect();
+    assert_eq!(all_impossible, vec![LogProb::from_raw_prob(0.25)?; 4]);
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_clamped_reported_test() -> Result<()> {
+    let (sum, clamped) = [0.5, 0.5, 0.3]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .iter()
+        .log_sum_exp_clamped_reported();
+    assert_eq!(sum, LogProb::new(0.0)?);
+    assert!(clamped);
+
+    let (sum, clamped) = [0.5, 0.2, 0.1]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .into_iter()
+        .log_sum_exp_clamped_reported();
+    approx::assert_relative_eq!(sum.into_inner(), 0.8_f64.ln());
+    assert!(!clamped);
+    Ok(())
+}
+
+#[test]
+fn divergences() -> Result<()> {
+    let p = [0.5, 0.3, 0.2, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let q = [0.25, 0.25, 0.25, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+
+    let kl = kl_divergence(&p, &q)?;
+    let expected: f64 = [0.5_f64, 0.3, 0.2]
+        .iter()
+        .map(|x| x * (x / 0.25).ln())
+        .sum();
+    approx::assert_relative_eq!(kl, expected);
+    assert_eq!(kl_divergence(&q, &p)?, f64::INFINITY);
+
+    approx::assert_relative_eq!(renyi_divergence(&p, &q, 1.0)?, kl);
+    approx::assert_relative_eq!(renyi_divergence(&p, &q, 1.0 + 1e-7)?, kl, epsilon = 1e-6);
+    for alpha in [0.5, 2.0, 5.0] {
+        approx::assert_relative_eq!(renyi_divergence(&p, &p, alpha)?, 0.0, epsilon = 1e-12);
+    }
+    let expected: f64 = ([0.5_f64, 0.3, 0.2]
+        .iter()
+        .map(|x| x * x / 0.25)
+        .sum::<f64>())
+    .ln();
+    approx::assert_relative_eq!(renyi_divergence(&p, &q, 2.0)?, expected, epsilon = 1e-12);
+    assert_eq!(renyi_divergence(&q, &p, 2.0)?, f64::INFINITY);
+
+    assert!(kl_divergence(&p, &q[..2]).is_err());
+    assert!(renyi_divergence(&p, &q[..2], 2.0).is_err());
+    Ok(())
+}
+
+#[test]
+fn chunked_log_sum_exp() -> Result<()> {
+    let v: Vec<_> = [0.1, 0.05, 0.2, 0.0, 0.15, 0.3, 0.1]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .to_vec();
+
+    let mut single = ChunkedLogSumExp::new();
+    single.add_chunk(&v);
+    approx::assert_relative_eq!(single.finish().into_inner(), log_sum_exp(&v)?.into_inner());
+
+    let mut chunked = ChunkedLogSumExp::default();
+    for chunk in v.chunks(3) {
+        chunked.add_chunk(chunk);
+    }
+    chunked.add_chunk::<LogProb<f64>>(&[]);
+    approx::assert_relative_eq!(
+        chunked.finish().into_inner(),
+        single.finish().into_inner(),
+        epsilon = 1e-12
+    );
+
+    let mut overflow = ChunkedLogSumExp::new();
+    overflow.add_chunk(&v);
+    overflow.add_chunk(&v);
+    assert_eq!(overflow.finish(), LogProb::new(0.0)?);
+
+    let mut empty = ChunkedLogSumExp::new();
+    empty.add_chunk(&[LogProb::new(f64::NEG_INFINITY)?]);
+    assert_eq!(empty.finish(), LogProb::new(f64::NEG_INFINITY)?);
+    Ok(())
+}
+
+#[test]
+fn total_variation() -> Result<()> {
+    let p = [0.5, 0.3, 0.2].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let q = [0.2, 0.3, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(total_variation_distance(&p, &q)?, 0.3);
+    approx::assert_relative_eq!(p[0].prob_abs_diff(&q[0]), 0.3);
+    assert_eq!(total_variation_distance(&p, &p)?, 0.0);
+
+    let a = [1.0, 0.0, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let b = [0.0, 0.0, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(total_variation_distance(&a, &b)?, 1.0);
+    assert_eq!(total_variation_distance(&a, &a)?, 0.0);
+    assert!(total_variation_distance(&a, &b[..2]).is_err());
+    Ok(())
+}
+
+#[test]
+fn hellinger() -> Result<()> {
+    let p = [0.5, 0.3, 0.2, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let q = [0.1, 0.1, 0.4, 0.4].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(hellinger_distance(&p, &p)?, 0.0, epsilon = 1e-7);
+    let coefficient: f64 = [0.05_f64, 0.03, 0.08].iter().map(|x| x.sqrt()).sum();
+    approx::assert_relative_eq!(hellinger_distance(&p, &q)?, (1.0 - coefficient).sqrt());
+
+    let a = [1.0, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let b = [0.0, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(hellinger_distance(&a, &b)?, 1.0);
+    assert!(hellinger_distance(&a, &q).is_err());
+    Ok(())
+}
+
+#[test]
+fn bhattacharyya_coefficient() -> Result<()> {
+    let p = [0.5, 0.3, 0.2, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let q = [0.1, 0.1, 0.4, 0.4].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(
+        bhattacharyya(&p, &p)?.into_inner(),
+        LogProb::certain().into_inner(),
+        epsilon = 1e-12
+    );
+    let coefficient: f64 = [0.05_f64, 0.03, 0.08].iter().map(|x| x.sqrt()).sum();
+    approx::assert_relative_eq!(bhattacharyya(&p, &q)?.raw_prob(), coefficient);
+
+    let a = [1.0, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let b = [0.0, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(bhattacharyya(&a, &b)?, LogProb::impossible());
+    assert!(bhattacharyya(&a, &q).is_err());
+    Ok(())
+}
+
+#[test]
+fn argsort() -> Result<()> {
+    let v = [0.1, 0.3, 0.0, 0.3, 0.3]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .to_vec();
+    assert_eq!(argsort_desc(&v), vec![1, 3, 4, 0, 2]);
+    assert_eq!(v[0], LogProb::from_raw_prob(0.1)?);
+
+    let v = [LogProb::new(-0.0)?, LogProb::new(0.0)?];
+    assert_eq!(argsort_desc(&v), vec![1, 0]);
+    assert!(argsort_desc::<f64>(&[]).is_empty());
+
+    // SAFETY: deliberately invalid, to check that sorting doesn't panic on NaN.
+    let nan = unsafe { LogProb::new_unchecked(f64::NAN) };
+    let v = [LogProb::new(-1.0)?, nan, LogProb::new(-0.5)?];
+    assert_eq!(argsort_desc(&v), vec![1, 2, 0]);
+    Ok(())
+}
+
+#[test]
+fn signed_powers() -> Result<()> {
+    let x = LogProb::new(-1.5_f64)?;
+    assert_eq!(x.powi(-1), 1.5);
+    assert_eq!(x.powi(3), -4.5);
+    assert_eq!(x.powi(0), 0.0);
+    assert_eq!(LogProb::<f32>::impossible().powi(0), 0.0);
+    assert_eq!(LogProb::<f32>::impossible().powi(-2), f32::INFINITY);
+    assert_eq!(LogProb::<f32>::impossible().powi(2), f32::NEG_INFINITY);
+    Ok(())
+}
+
+#[test]
+fn serde_errors() -> Result<()> {
+    let err = LogProb::new(1.0_f64).unwrap_err();
+    let json = serde_json::to_string(&err)?;
+    assert_eq!(serde_json::from_str::<FloatIsNanOrPositive>(&json)?, err);
+
+    let err = LogProb::new(-0.5_f64)?
+        .add_log_prob(LogProb::new(-0.5)?)
+        .unwrap_err();
+    let json = serde_json::to_string(&err)?;
+    assert_eq!(
+        serde_json::from_str::<ProbabilitiesSumToGreaterThanOne>(&json)?,
+        err
+    );
+    Ok(())
+}
+
+#[test]
+fn float_slice_reinterpretation() -> Result<()> {
+    let v = vec![-1.0_f64, 0.0, -0.0, f64::NEG_INFINITY, -3.5];
+    let log_probs = from_float_slice(&v).unwrap();
+    assert_eq!(log_probs[4], LogProb::new(-3.5)?);
+    assert_eq!(log_probs.as_ptr().cast::<f64>(), v.as_ptr());
+
+    let floats = as_float_slice(log_probs);
+    assert_eq!(floats, &v[..]);
+    assert_eq!(floats.as_ptr(), v.as_ptr());
+
+    assert_eq!(
+        from_float_slice(&[-1.0_f32, 0.5, f32::NAN]).unwrap_err(),
+        (1, FloatIsNanOrPositive)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn fast_softmax() -> Result<()> {
+    use rand::prelude::*;
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    for n in [0, 1, 7, 8, 9, 100, 4099] {
+        let x: Vec<f32> = (0..n).map(|_| rng.gen::<f32>() * 40.0 - 20.0).collect();
+        let fast = logprob::softmax_f32_fast(&x)?;
+        let slow: Vec<_> = softmax(&x)?.collect();
+        assert_eq!(fast.len(), slow.len());
+        for (a, b) in fast.iter().zip(slow.iter()) {
+            approx::assert_abs_diff_eq!(a.into_inner(), b.into_inner(), epsilon = 1e-5);
+        }
+    }
+    let x = [0.0, f32::NEG_INFINITY, -100.0, -1.0];
+    let fast = logprob::softmax_f32_fast(&x)?;
+    assert_eq!(fast[1], LogProb::impossible());
+    approx::assert_relative_eq!(
+        fast[2].into_inner(),
+        -100.0 - 1.0_f32.exp().ln_1p() + 1.0,
+        epsilon = 1e-4
+    );
+    assert!(logprob::softmax_f32_fast(&[0.0, f32::NAN]).is_err());
+    assert!(logprob::softmax_f32_fast(&[0.0, f32::INFINITY]).is_err());
+    Ok(())
+}
+
+#[test]
+fn raw_log_sum_exp() -> Result<()> {
+    let v: Vec<_> = [0.5, 0.5, 0.3, 0.0]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .to_vec();
+    let raw: Vec<f64> = v.iter().map(|x| x.into_inner()).collect();
+    approx::assert_relative_eq!(log_sum_exp_raw(&raw), log_sum_exp_float(&v));
+    approx::assert_relative_eq!(log_sum_exp_raw(&[3.0_f32, 3.0]), 3.0 + 2.0_f32.ln());
+    assert_eq!(log_sum_exp_raw::<f64>(&[]), f64::NEG_INFINITY);
+    assert_eq!(log_sum_exp_raw(&[f64::NEG_INFINITY; 2]), f64::NEG_INFINITY);
+    Ok(())
+}
+
+#[test]
+fn distribution_table() -> Result<()> {
+    let v = [0.5, 0.0, 0.25, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let table = format_distribution(&["heads", "edge", "tails", "lost"], &v);
+    let expected = [
+        "heads:  0.5 (-0.6931471805599453)",
+        "tails: 0.25 (-1.3862943611198906)",
+        "lost : 0.25 (-1.3862943611198906)",
+        "edge :    0                (-inf)",
+    ]
+    .join("\n");
+    assert_eq!(table, expected);
+
+    let v = [0.125_f32, 0.5, 0.375].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let table = format_distribution(&[1, 2, 3], &v);
+    let expected = [
+        "2:   0.5  (-0.6931472)",
+        "3: 0.375 (-0.98082924)",
+        "1: 0.125  (-2.0794415)",
+    ]
+    .join("\n");
+    assert_eq!(table, expected);
+    assert_eq!(format_distribution::<f64, &str>(&[], &[]), "");
+    Ok(())
+}
+
+#[test]
+fn narrowing_raw_probs() -> Result<()> {
+    let probs = [1.0, 0.5, 1e-10, 1e-40, 1e-300, 0.0];
+    let v = from_raw_probs_f64_to_f32(&probs).unwrap();
+    for (x, p) in v.iter().zip(probs) {
+        approx::assert_relative_eq!(x.into_inner(), p.ln() as f32);
+    }
+    assert!(v[3].into_inner().is_finite());
+    approx::assert_relative_eq!(v[3].into_inner(), -92.103_4, epsilon = 1e-3);
+    assert!(v[4].into_inner().is_finite());
+    assert_eq!(v[5], LogProb::impossible());
+    assert_eq!(
+        LogProb::from_raw_prob(1e-50_f64 as f32)?,
+        LogProb::impossible()
+    );
+    assert!(from_raw_probs_f64_to_f32(&[1e-50]).unwrap()[0]
+        .into_inner()
+        .is_finite());
+
+    assert_eq!(
+        from_raw_probs_f64_to_f32(&[0.5, 1.5, f64::NAN]).unwrap_err(),
+        (1, FloatIsNanOrPositive)
+    );
+    assert_eq!(
+        from_raw_probs_f64_to_f32(&[0.5, -0.1]).unwrap_err(),
+        (1, FloatIsNanOrPositive)
+    );
+    Ok(())
+}
+
+#[test]
+fn min_heap() -> Result<()> {
+    use std::collections::BinaryHeap;
+    let probs = [0.3, 0.05, 0.0, 0.5, 0.15];
+    let mut heap = BinaryHeap::new();
+    for p in probs {
+        heap.push(MinProb::from(LogProb::from_raw_prob(p)?));
+    }
+    let popped: Vec<f64> = std::iter::from_fn(|| heap.pop())
+        .map(|x| x.0.raw_prob())
+        .collect();
+    approx::assert_relative_eq!(popped[..], [0.0, 0.05, 0.15, 0.3, 0.5][..]);
+
+    let mut heap: BinaryHeap<LogProb<f64>> = probs
+        .iter()
+        .map(|&p| LogProb::from_raw_prob(p).unwrap())
+        .collect();
+    approx::assert_relative_eq!(heap.pop().unwrap().raw_prob(), 0.5);
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_by_test() -> Result<()> {
+    let model = |x: u32| LogProb::new(-f64::from(x)).unwrap();
+    let fused = (1..20).log_sum_exp_by(model)?;
+    let explicit = (1..20).map(model).log_sum_exp_no_alloc()?;
+    assert_eq!(fused, explicit);
+    approx::assert_relative_eq!(
+        fused.into_inner(),
+        (1..20).map(model).log_sum_exp()?.into_inner(),
+        epsilon = 1e-12
+    );
+    assert!((0..3)
+        .log_sum_exp_by(|_| LogProb::new(-0.5).unwrap())
+        .is_err());
+    assert_eq!(
+        std::iter::empty::<u32>().log_sum_exp_by(model)?,
+        LogProb::impossible()
+    );
+    Ok(())
+}
+
+#[test]
+fn likelihood_ratios() -> Result<()> {
+    let p = [0.5, 0.25, 0.25, 0.0, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let q = [0.25, 0.5, 0.0, 0.25, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let ratios = log_ratios(&p, &q)?;
+    approx::assert_relative_eq!(ratios[0], 2.0_f64.ln());
+    approx::assert_relative_eq!(ratios[1], 0.5_f64.ln());
+    assert_eq!(ratios[2], f64::INFINITY);
+    assert_eq!(ratios[3], f64::NEG_INFINITY);
+    assert!(ratios[4].is_nan());
+    assert!(log_ratios(&p, &q[1..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn log_prior() -> Result<()> {
+    let likelihood = [0.9, 0.5, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let uniform = [LogProb::certain(); 3];
+    let posterior: Vec<_> = likelihood.iter().apply_log_prior(&uniform).collect();
+    assert_eq!(posterior, likelihood);
+
+    let skewed = [0.1, 0.0, 0.9].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let posterior: Vec<_> = likelihood
+        .into_iter()
+        .apply_log_prior(&skewed[..2])
+        .collect();
+    assert_eq!(posterior.len(), 2);
+    approx::assert_relative_eq!(posterior[0].raw_prob(), 0.09);
+    assert_eq!(posterior[1], LogProb::impossible());
+    Ok(())
+}
+
+#[test]
+fn bayes_rule() -> Result<()> {
+    let likelihood = [0.2, 0.6, 0.2, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let uniform = [0.25; 4].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let posterior = bayesian_update(&uniform, &likelihood)?;
+    for (a, b) in posterior.iter().zip(likelihood) {
+        approx::assert_relative_eq!(a.raw_prob(), b.raw_prob(), epsilon = 1e-12);
+    }
+    assert_eq!(posterior[3], LogProb::impossible());
+
+    let prior = [0.5, 0.3, 0.2, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let posterior = bayesian_update(&prior, &likelihood)?;
+    let evidence = 0.5 * 0.2 + 0.3 * 0.6 + 0.2 * 0.2;
+    approx::assert_relative_eq!(posterior[0].raw_prob(), 0.1 / evidence, epsilon = 1e-12);
+    approx::assert_relative_eq!(posterior[1].raw_prob(), 0.18 / evidence, epsilon = 1e-12);
+    approx::assert_relative_eq!(posterior[2].raw_prob(), 0.04 / evidence, epsilon = 1e-12);
+    assert_eq!(posterior[3], LogProb::impossible());
+
+    let zero = [0.0, 0.0, 0.0, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let posterior = bayesian_update(&prior, &zero)?;
+    assert!(posterior.iter().all(|x| *x == LogProb::impossible()));
+    assert!(bayesian_update(&prior, &likelihood[..3]).is_err());
+    Ok(())
+}
+
+#[test]
+fn sequential_bayes() -> Result<()> {
+    let p = |x: f64| LogProb::from_raw_prob(x).unwrap();
+    let prior = [p(0.5), p(0.3), p(0.2)];
+    let likelihoods = [
+        [p(0.1), p(0.5), p(0.9)],
+        [p(0.2), p(0.4), p(0.3)],
+        [p(0.7), p(0.1), p(0.05)],
+    ];
+    let mut state = BeliefState::new(&prior);
+    for likelihood in &likelihoods {
+        state.update(likelihood)?;
+    }
+
+    let mut batch = prior.to_vec();
+    for likelihood in &likelihoods {
+        batch = batch.iter().zip(likelihood).map(|(&b, &l)| b + l).collect();
+    }
+    let evidence = log_sum_exp(&batch)?;
+    for (a, b) in state.belief().iter().zip(&batch) {
+        approx::assert_relative_eq!(
+            a.into_inner(),
+            b.into_inner() - evidence.into_inner(),
+            epsilon = 1e-12
+        );
+    }
+    approx::assert_relative_eq!(state.log_evidence(), evidence.into_inner(), epsilon = 1e-12);
+    assert!(state.update(&[p(0.5)]).is_err());
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_or_clamp_test() -> Result<()> {
+    let (sum, overflow) = [0.5, 0.5, 0.3]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .iter()
+        .log_sum_exp_or_clamp();
+    assert_eq!(sum, LogProb::certain());
+    assert_eq!(overflow, Err(ProbabilitiesSumToGreaterThanOne));
+
+    let (sum, overflow) = [0.5, 0.3]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .into_iter()
+        .log_sum_exp_or_clamp();
+    approx::assert_relative_eq!(sum.into_inner(), 0.8_f64.ln());
+    overflow?;
+    Ok(())
+}
+
+#[test]
+fn zero_and_one_probs() -> Result<()> {
+    assert!(LogProb::<f64>::impossible().is_zero_prob());
+    assert!(!LogProb::<f64>::impossible().is_one_prob());
+    for x in [
+        LogProb::certain(),
+        LogProb::new(0.0_f32)?,
+        LogProb::new(-0.0_f32)?,
+    ] {
+        assert!(x.is_one_prob());
+        assert!(!x.is_zero_prob());
+    }
+    let x = LogProb::new(-1e-300_f64)?;
+    assert!(!x.is_one_prob());
+    assert!(!x.is_zero_prob());
+    assert!(LogProb::new(f64::MIN)?.opposite_prob().is_one_prob());
+    Ok(())
+}
+
+#[test]
+fn quantization() -> Result<()> {
+    for scale in [10.0_f64, 100.0, 1000.0] {
+        for i in 0..=300 {
+            let x = LogProb::new(-(i as f64) * 0.0137)?;
+            let q = x.quantize(scale);
+            let y = LogProb::dequantize(q, scale);
+            assert!((x.into_inner() - y.into_inner()).abs() <= 1.0 / scale);
+        }
+    }
+    assert_eq!(LogProb::<f32>::impossible().quantize(100.0), i16::MIN);
+    assert_eq!(
+        LogProb::<f32>::dequantize(i16::MIN, 100.0),
+        LogProb::impossible()
+    );
+    assert_eq!(LogProb::new(-1e10_f32)?.quantize(100.0), i16::MIN + 1);
+    assert!(!LogProb::dequantize(i16::MIN + 1, 100.0_f32).is_zero_prob());
+    assert_eq!(LogProb::certain().quantize(100.0_f32), 0);
+    assert_eq!(LogProb::dequantize(5, 100.0_f32), LogProb::certain());
+    Ok(())
+}
+
+#[test]
+fn joint_marginals() -> Result<()> {
+    let joint = [0.1, 0.2, 0.3, 0.05, 0.15, 0.2]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let (rows, cols) = marginals(&joint, 2, 3)?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(cols.len(), 3);
+    for (x, y) in rows.iter().zip([0.6, 0.4]) {
+        approx::assert_relative_eq!(x.raw_prob(), y);
+    }
+    for (x, y) in cols.iter().zip([0.15, 0.35, 0.5]) {
+        approx::assert_relative_eq!(x.raw_prob(), y);
+    }
+    approx::assert_abs_diff_eq!(
+        rows.iter().log_sum_exp_float_no_alloc(),
+        0.0,
+        epsilon = 1e-12
+    );
+    approx::assert_abs_diff_eq!(
+        cols.iter().log_sum_exp_float_no_alloc(),
+        0.0,
+        epsilon = 1e-12
+    );
+    assert_eq!(marginals(&joint, 4, 2), Err(DimensionMismatch));
+    Ok(())
+}
+
+#[test]
+fn joint_mutual_information() -> Result<()> {
+    let p = [0.2, 0.8];
+    let q = [0.1, 0.3, 0.6];
+    let independent = p
+        .iter()
+        .flat_map(|x| q.iter().map(move |y| LogProb::from_raw_prob(x * y)))
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    approx::assert_abs_diff_eq!(
+        mutual_information(&independent, 2, 3)?,
+        0.0,
+        epsilon = 1e-12
+    );
+
+    let half = LogProb::from_raw_prob(0.5_f64)?;
+    let diagonal = [half, LogProb::impossible(), LogProb::impossible(), half];
+    approx::assert_relative_eq!(mutual_information(&diagonal, 2, 2)?, 2.0_f64.ln());
+    assert_eq!(mutual_information(&diagonal, 3, 2), Err(DimensionMismatch));
+    Ok(())
+}
+
+#[test]
+fn joint_conditional() -> Result<()> {
+    let joint = [0.1, 0.2, 0.3, 0.0, 0.0, 0.0, 0.05, 0.05, 0.3]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let c = conditional(&joint, 3, 3, 0)?;
+    approx::assert_abs_diff_eq!(c.iter().log_sum_exp_float_no_alloc(), 0.0, epsilon = 1e-12);
+    for (x, y) in c.iter().zip([1.0 / 6.0, 1.0 / 3.0, 0.5]) {
+        approx::assert_relative_eq!(x.raw_prob(), y);
+    }
+    let c = conditional(&joint, 3, 3, 2)?;
+    approx::assert_relative_eq!(c[2].raw_prob(), 0.75);
+
+    assert_eq!(
+        conditional(&joint, 3, 3, 1),
+        Err(ConditioningError::ImpossibleCondition)
+    );
+    assert_eq!(
+        conditional(&joint, 3, 3, 3),
+        Err(ConditioningError::RowOutOfBounds)
+    );
+    assert_eq!(
+        conditional(&joint, 2, 3, 0),
+        Err(ConditioningError::DimensionMismatch)
+    );
+    Ok(())
+}
+
+#[test]
+fn linspace() -> Result<()> {
+    let start = LogProb::new(-10.0_f64)?;
+    let end = LogProb::new(-0.5_f64)?;
+    let grid: Vec<_> = log_prob_linspace(start, end, 20).collect();
+    assert_eq!(grid.len(), 20);
+    assert_eq!(grid[0], start);
+    assert_eq!(grid[19], end);
+    for w in grid.windows(3) {
+        approx::assert_relative_eq!(
+            w[1].into_inner() - w[0].into_inner(),
+            w[2].into_inner() - w[1].into_inner(),
+            epsilon = 1e-12
+        );
+    }
+
+    let grid: Vec<_> = log_prob_linspace(end, LogProb::impossible(), 5).collect();
+    assert_eq!(grid.len(), 5);
+    assert_eq!(grid[0], end);
+    assert_eq!(grid[4], LogProb::impossible());
+    assert!(grid.windows(2).all(|w| w[0] > w[1]));
+
+    assert_eq!(log_prob_linspace(start, end, 0).count(), 0);
+    assert_eq!(
+        log_prob_linspace(start, end, 1).collect::<Vec<_>>(),
+        vec![start]
+    );
+    Ok(())
+}
+
+#[test]
+fn sorted_desc_clamped() -> Result<()> {
+    let mut x = [0.1, 0.05, 0.3, 0.2, 0.01].map(|x| LogProb::from_raw_prob(x).unwrap());
+    x.sort_by(|a, b| b.cmp(a));
+    approx::assert_relative_eq!(
+        x.iter().log_sum_exp_clamped_sorted_desc().into_inner(),
+        x.iter().log_sum_exp_clamped_no_alloc().into_inner(),
+        max_relative = 1e-12
+    );
+    assert_eq!(
+        Vec::<LogProb<f64>>::new()
+            .iter()
+            .log_sum_exp_clamped_sorted_desc(),
+        LogProb::impossible()
+    );
+    assert_eq!(
+        [LogProb::<f64>::impossible(); 3]
+            .iter()
+            .log_sum_exp_clamped_sorted_desc(),
+        LogProb::impossible()
+    );
+
+    let mut x = [0.1, 0.5, 0.3, 0.4, 0.01].map(|x| LogProb::from_raw_prob(x).unwrap());
+    x.sort_by(|a, b| b.cmp(a));
+    let mut pulled = 0;
+    let sum = x
+        .iter()
+        .inspect(|_| pulled += 1)
+        .log_sum_exp_clamped_no_alloc();
+    assert_eq!(sum, LogProb::certain());
+    assert_eq!(pulled, 3);
+    let mut pulled = 0;
+    let sum = x
+        .iter()
+        .inspect(|_| pulled += 1)
+        .log_sum_exp_clamped_sorted_desc();
+    assert_eq!(sum, LogProb::certain());
+    assert_eq!(pulled, 3);
+    Ok(())
+}
+
+#[test]
+fn continuous_log_pdfs() -> Result<()> {
+    let half_log_tau = 0.5 * std::f64::consts::TAU.ln();
+    approx::assert_relative_eq!(normal_log_pdf(0.0, 0.0, 1.0).into_inner(), -half_log_tau);
+    approx::assert_relative_eq!(
+        normal_log_pdf(3.0, 1.0, 2.0).into_inner(),
+        -0.5 - 2.0_f64.ln() - half_log_tau
+    );
+    approx::assert_relative_eq!(
+        normal_log_pdf(0.0_f32, 0.0, 0.01).density(),
+        39.894_23,
+        max_relative = 1e-5
+    );
+    assert_eq!(
+        normal_log_pdf(f64::INFINITY, 0.0, 1.0).into_inner(),
+        f64::NEG_INFINITY
+    );
+
+    let log_pi = std::f64::consts::PI.ln();
+    approx::assert_relative_eq!(
+        cauchy_log_pdf(1.0, 0.0, 1.0).into_inner(),
+        -log_pi - 2.0_f64.ln()
+    );
+    approx::assert_relative_eq!(
+        cauchy_log_pdf(-1.0, 1.0, 2.0).into_inner(),
+        -log_pi - 2.0_f64.ln() - 2.0_f64.ln()
+    );
+    approx::assert_relative_eq!(
+        cauchy_log_pdf(1e200, 0.0, 1.0).into_inner(),
+        -log_pi - 400.0 * 10.0_f64.ln()
+    );
+
+    assert_eq!(LogDensity::new(f64::NAN), Err(FloatIsNan));
+    assert_eq!(LogDensity::new(3.0)?.into_inner(), 3.0);
+    assert_eq!(
+        LogDensity::from(LogProb::new(-1.0_f64)?),
+        LogDensity::new(-1.0)?
+    );
+    assert_eq!(format!("{:.2}", LogDensity::new(1.23456)?), "1.23");
+    assert_eq!(format!("{:>6.1}", LogDensity::new(-2.25_f32)?), "  -2.2");
+    Ok(())
+}
+
+#[test]
+fn numpy_logaddexp() -> Result<()> {
+    approx::assert_relative_eq!(
+        logaddexp(1e-50_f64.ln(), 2.5e-50_f64.ln()),
+        3.5e-50_f64.ln()
+    );
+    approx::assert_relative_eq!(logaddexp(0.0_f64, 0.0), 2.0_f64.ln());
+    approx::assert_relative_eq!(logaddexp(1.0_f64, 2.0), 2.313_261_687_518_223);
+    approx::assert_relative_eq!(logaddexp(-1000.0_f64, -1000.0), -1000.0 + 2.0_f64.ln());
+    assert_eq!(
+        logaddexp(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        f64::NEG_INFINITY
+    );
+    assert_eq!(logaddexp(f64::NEG_INFINITY, -3.0), -3.0);
+    assert_eq!(logaddexp(-3.0_f32, f32::NEG_INFINITY), -3.0);
+    assert_eq!(logaddexp(f64::INFINITY, 1.0), f64::INFINITY);
+    assert_eq!(logaddexp(f64::INFINITY, f64::NEG_INFINITY), f64::INFINITY);
+    assert!(logaddexp(f64::NAN, 1.0).is_nan());
+    Ok(())
+}
+
+#[test]
+fn scipy_logsubexp() -> Result<()> {
+    approx::assert_relative_eq!(
+        logsubexp(2.0_f64, 1.0),
+        (2.0_f64.exp() - 1.0_f64.exp()).ln()
+    );
+    approx::assert_relative_eq!(
+        logsubexp(0.0_f64, -1e-10),
+        (1e-10_f64).ln(),
+        max_relative = 1e-9
+    );
+    approx::assert_relative_eq!(
+        logsubexp(-1000.0_f64, -1001.0),
+        -1000.0 + (-(-1.0_f64).exp_m1()).ln()
+    );
+    approx::assert_relative_eq!(logsubexp(0.0_f32, -50.0), 0.0);
+    approx::assert_relative_eq!(logaddexp(logsubexp(-2.0_f64, -3.0), -3.0), -2.0);
+    assert_eq!(logsubexp(-3.0_f64, f64::NEG_INFINITY), -3.0);
+    assert_eq!(
+        logsubexp(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        f64::NEG_INFINITY
+    );
+    assert_eq!(logsubexp(-3.0_f64, -3.0), f64::NEG_INFINITY);
+    assert_eq!(logsubexp(f64::INFINITY, 3.0), f64::INFINITY);
+    assert!(logsubexp(-3.0_f64, -2.0).is_nan());
+    assert!(logsubexp(f64::NEG_INFINITY, -2.0).is_nan());
+    assert!(logsubexp(f64::INFINITY, f64::INFINITY).is_nan());
+    Ok(())
+}
+
+#[test]
+fn running_normalization() -> Result<()> {
+    let x = [0.0, 0.1, 0.4, 0.2, 0.3]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let running: Vec<_> = x.iter().running_normalized().collect();
+    assert_eq!(running.len(), x.len());
+    assert_eq!(running[0], LogProb::impossible());
+    assert_eq!(running[1], LogProb::certain());
+    approx::assert_relative_eq!(running[2].raw_prob(), 0.8);
+
+    let batch: Vec<_> = softmax(&x.iter().map(|x| x.into_inner()).collect::<Vec<_>>())?.collect();
+    approx::assert_relative_eq!(running[4].into_inner(), batch[4].into_inner());
+    assert_eq!(
+        [LogProb::<f32>::impossible()]
+            .iter()
+            .running_normalized()
+            .collect::<Vec<_>>(),
+        vec![LogProb::impossible()]
+    );
+    Ok(())
+}
+
+#[test]
+fn histogram() -> Result<()> {
+    let x = [
+        0.0,
+        -0.1,
+        -0.9,
+        -1.0,
+        -2.5,
+        -3.9,
+        -4.0,
+        -7.0,
+        f64::NEG_INFINITY,
+    ]
+    .map(|x| LogProb::new(x).unwrap());
+    let lo = LogProb::new(-4.0)?;
+    let hi = LogProb::new(0.0)?;
+    let counts = prob_histogram(&x, 4, lo, hi);
+    assert_eq!(counts, vec![4, 1, 0, 4]);
+    assert_eq!(counts.iter().sum::<usize>(), x.len());
+    assert_eq!(prob_histogram(&x, 1, lo, hi), vec![x.len()]);
+    assert!(prob_histogram(&x, 0, lo, hi).is_empty());
+    assert_eq!(prob_histogram(&x, 2, lo, LogProb::new(-2.0)?), vec![4, 5]);
+    Ok(())
+}
+
+#[test]
+fn refined_log_sum_exp() -> Result<()> {
+    let n = 1_000_000;
+    let raw: Vec<f64> = (0..n)
+        .map(|i| (1.0 + f64::from(i % 7) * 0.1) / f64::from(n) / 2.6)
+        .collect();
+    let reference = raw.iter().sum::<f64>().ln();
+    let x: Vec<LogProb<f32>> = raw
+        .iter()
+        .map(|&p| LogProb::new(p.ln() as f32))
+        .collect::<Result<_, _>>()?;
+    // The inputs themselves are rounded to f32, so compare against the sum of the rounded inputs.
+    let reference_rounded = x
+        .iter()
+        .map(|x| f64::from(x.into_inner()).exp())
+        .sum::<f64>()
+        .ln();
+    approx::assert_abs_diff_eq!(reference, reference_rounded, epsilon = 1e-6);
+
+    let standard = f64::from(x.iter().log_sum_exp()?.into_inner());
+    let refined = f64::from(x.iter().log_sum_exp_refined()?.into_inner());
+    let standard_error = (standard - reference_rounded).abs();
+    let refined_error = (refined - reference_rounded).abs();
+    assert!(refined_error < standard_error / 10.0);
+    assert!(refined_error < 1e-6);
+
+    let half = LogProb::from_raw_prob(0.5_f64)?;
+    assert_eq!(
+        [half, half].iter().log_sum_exp_refined()?,
+        LogProb::certain()
+    );
+    assert!([half, half, half].iter().log_sum_exp_refined().is_err());
+    assert_eq!(
+        Vec::<LogProb<f64>>::new().iter().log_sum_exp_refined()?,
+        LogProb::impossible()
+    );
+    Ok(())
+}
+
+#[test]
+fn support() -> Result<()> {
+    let x = [
+        f64::NEG_INFINITY,
+        -1.0,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+        0.0,
+        -300.0,
+        f64::NEG_INFINITY,
+    ]
+    .map(|x| LogProb::new(x).unwrap());
+    assert_eq!(support_indices(&x).collect::<Vec<_>>(), vec![1, 4, 5]);
+    assert_eq!(support_size(&x), 3);
+    assert_eq!(support_indices::<f32>(&[]).count(), 0);
+    assert_eq!(support_size(&[LogProb::<f32>::impossible(); 4]), 0);
+    Ok(())
+}
+
+#[test]
+fn renormalizing_support() -> Result<()> {
+    let mut x = [f64::NEG_INFINITY; 10].map(|x| LogProb::new(x).unwrap());
+    x[2] = LogProb::from_raw_prob(0.1)?;
+    x[5] = LogProb::from_raw_prob(0.3)?;
+    x[9] = LogProb::from_raw_prob(0.4)?;
+    renormalize_support(&mut x);
+    approx::assert_abs_diff_eq!(x.iter().log_sum_exp_float_no_alloc(), 0.0, epsilon = 1e-12);
+    approx::assert_relative_eq!(x[5].raw_prob(), 0.375);
+    for i in support_indices(&x).collect::<Vec<_>>() {
+        assert!([2, 5, 9].contains(&i));
+    }
+    assert_eq!(support_size(&x), 3);
+
+    let mut empty = [LogProb::<f32>::impossible(); 3];
+    renormalize_support(&mut empty);
+    assert_eq!(empty, [LogProb::impossible(); 3]);
+    Ok(())
+}
+
+#[test]
+fn partitioning_sorted() -> Result<()> {
+    let v = [f64::NEG_INFINITY, -5.0, -2.0, -2.0, -1.0, -0.0].map(|x| LogProb::new(x).unwrap());
+    assert_eq!(partition_above(&v, LogProb::new(-3.0)?), 2);
+    assert_eq!(partition_above(&v, LogProb::new(-2.0)?), 4);
+    assert_eq!(partition_above(&v, LogProb::impossible()), 1);
+    assert_eq!(partition_above(&v, LogProb::new(-10.0)?), 1);
+    assert_eq!(partition_above(&v, LogProb::new(0.0)?), v.len());
+    assert_eq!(partition_above(&[], LogProb::new(-1.0_f32)?), 0);
+    Ok(())
+}
+
+#[test]
+fn raw_probs_to_log_probs() -> Result<()> {
+    let v = from_raw_probs(&[0.0, 0.5, 1.0]).unwrap();
+    assert_eq!(
+        v,
+        vec![
+            LogProb::impossible(),
+            LogProb::new(0.5_f64.ln())?,
+            LogProb::certain()
+        ]
+    );
+    assert_eq!(
+        from_raw_probs(&[0.5, f64::NAN, 2.0]),
+        Err((1, FloatIsNanOrPositive))
+    );
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_raw_probs_to_log_probs() -> Result<()> {
+    use logprob::par_from_raw_probs;
+    let raw: Vec<f64> = (0..100_000).map(|i| f64::from(i) / 100_000.0).collect();
+    assert_eq!(par_from_raw_probs(&raw), from_raw_probs(&raw));
+    assert!(par_from_raw_probs(&raw).is_ok());
+
+    let mut raw = raw;
+    raw[70_000] = 1.5;
+    raw[99_999] = -0.5;
+    raw[31_234] = f64::NAN;
+    for _ in 0..10 {
+        assert_eq!(
+            par_from_raw_probs(&raw),
+            Err((31_234, FloatIsNanOrPositive))
+        );
+    }
+    assert_eq!(par_from_raw_probs(&raw), from_raw_probs(&raw));
+    Ok(())
+}
+
+#[test]
+fn filtering_possible() -> Result<()> {
+    let x = [
+        f64::NEG_INFINITY,
+        -1.0,
+        f64::NEG_INFINITY,
+        -2.0,
+        0.0,
+        f64::NEG_INFINITY,
+    ]
+    .map(|x| LogProb::new(x).unwrap());
+    let possible: Vec<_> = x.iter().filter_possible().collect();
+    assert_eq!(
+        possible,
+        vec![LogProb::new(-1.0)?, LogProb::new(-2.0)?, LogProb::new(0.0)?]
+    );
+    assert_eq!(
+        x.into_iter().filter_possible().log_sum_exp_float(),
+        x.iter().log_sum_exp_float()
+    );
+    assert_eq!(
+        [LogProb::<f32>::impossible(); 3]
+            .iter()
+            .filter_possible()
+            .count(),
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn absorbing_addition() -> Result<()> {
+    let x = LogProb::new(-2.0_f64)?;
+    let zero = LogProb::impossible();
+    assert_eq!(x.add_warn_absorbing(&x), (LogProb::new(-4.0)?, false));
+    assert_eq!(x.add_warn_absorbing(&zero), (zero, true));
+    assert_eq!(zero.add_warn_absorbing(&x), (zero, true));
+    assert_eq!(zero.add_warn_absorbing(&zero), (zero, true));
+    let (y, absorbed) = LogProb::new(-800.0_f64)?.add_warn_absorbing(&LogProb::new(-800.0)?);
+    assert!(!absorbed);
+    assert_eq!(y.raw_prob(), 0.0);
+    Ok(())
+}
+
+#[test]
+fn online_mean() -> Result<()> {
+    let x: Vec<f64> = (0..1000)
+        .map(|i| -f64::from(i % 13) * 0.37 - 1e-3)
+        .collect();
+    let mut mean = OnlineMean::new();
+    for &v in &x {
+        mean.push(LogProb::new(v)?);
+        if v < -4.0 {
+            mean.push(LogProb::impossible());
+        }
+    }
+    let batch = x.iter().sum::<f64>() / x.len() as f64;
+    approx::assert_relative_eq!(mean.mean(), batch, max_relative = 1e-12);
+    assert_eq!(mean.count(), x.len());
+    assert_eq!(mean.n_impossible(), x.iter().filter(|&&v| v < -4.0).count());
+
+    let empty = OnlineMean::<f32>::default();
+    assert!(empty.mean().is_nan());
+    assert_eq!(empty.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn prefix_log_sum_exp() -> Result<()> {
+    let x = [0.05, 0.1, 0.0, 0.2, 0.15, 0.3, 0.2]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let prefix = PrefixLogSumExp::new(&x);
+    assert_eq!(prefix.len(), x.len());
+    assert_eq!(prefix.prefix(0), LogProb::impossible());
+    for (i, c) in log_cumsum_exp(&x).into_iter().enumerate() {
+        assert_eq!(prefix.prefix(i + 1), c);
+    }
+    for (i, j) in [(0, 7), (0, 3), (2, 3), (1, 5), (3, 7), (6, 7), (4, 4)] {
+        approx::assert_abs_diff_eq!(
+            prefix.range(i, j).raw_prob(),
+            x[i..j].iter().log_sum_exp_float_no_alloc().exp(),
+            epsilon = 1e-12
+        );
+    }
+    assert_eq!(prefix.range(2, 3), LogProb::impossible());
+
+    // The prefix before the range is so much larger that subtracting prefix sums would cancel.
+    let x = [-0.01_f64, -40.0, -45.0].map(|x| LogProb::new(x).unwrap());
+    let prefix = PrefixLogSumExp::new(&x);
+    approx::assert_relative_eq!(prefix.range(1, 2).into_inner(), -40.0);
+    approx::assert_relative_eq!(prefix.range(0, 1).into_inner(), -0.01);
+    approx::assert_relative_eq!(
+        prefix.range(1, 3).into_inner(),
+        logaddexp(-40.0, -45.0),
+        max_relative = 1e-12
+    );
+    assert!(PrefixLogSumExp::<f32>::new(&[]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn proper_distribution() -> Result<()> {
+    let x = [0.5, 0.75, 0.25, 0.0]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    assert!(x.iter().log_sum_exp().is_err());
+    let y = to_proper_distribution(&x);
+    assert!(y.iter().log_sum_exp().is_ok());
+    approx::assert_abs_diff_eq!(y.iter().log_sum_exp_float_no_alloc(), 0.0, epsilon = 1e-12);
+    approx::assert_relative_eq!(y[1].raw_prob(), 0.5);
+    assert_eq!(y[3], LogProb::impossible());
+
+    let under = [LogProb::from_raw_prob(0.1_f32)?; 4];
+    assert_eq!(
+        to_proper_distribution(&under),
+        vec![LogProb::from_raw_prob(0.25)?; 4]
+    );
+    let impossible = [LogProb::<f32>::impossible(); 2];
+    assert_eq!(to_proper_distribution(&impossible), impossible.to_vec());
+    Ok(())
+}
+
+#[cfg(feature = "num-rational")]
+#[test]
+fn ratio_probabilities() -> Result<()> {
+    use num_rational::Ratio;
+    assert_eq!(
+        LogProb::from_ratio(Ratio::new(1, 4))?,
+        LogProb::from_raw_prob(0.25)?
+    );
+    assert_eq!(
+        LogProb::from_ratio(Ratio::new(-3, -12))?,
+        LogProb::from_raw_prob(0.25)?
+    );
+    assert_eq!(
+        LogProb::from_ratio(Ratio::new(0, 7))?,
+        LogProb::impossible()
+    );
+    assert_eq!(LogProb::from_ratio(Ratio::new(7, 7))?, LogProb::certain());
+    assert!(LogProb::from_ratio(Ratio::new(5, 4)).is_err());
+    assert!(LogProb::from_ratio(Ratio::new(-1, 4)).is_err());
+    assert!(LogProb::from_ratio(Ratio::new(1, -4)).is_err());
+    Ok(())
+}
+
+#[test]
+fn convolution() -> Result<()> {
+    let die = [LogProb::from_raw_prob(1.0 / 6.0_f64)?; 6];
+    let two_dice = log_convolve(&die, &die);
+    assert_eq!(two_dice.len(), 11);
+    for (i, x) in two_dice.iter().enumerate() {
+        let ways = 6.0 - (i as f64 - 5.0).abs();
+        approx::assert_relative_eq!(x.raw_prob(), ways / 36.0, max_relative = 1e-12);
+    }
+    approx::assert_abs_diff_eq!(
+        two_dice.iter().log_sum_exp_float_no_alloc(),
+        0.0,
+        epsilon = 1e-12
+    );
+
+    let coin = [LogProb::from_raw_prob(0.5_f64)?; 2];
+    let skewed = [0.2, 0.8].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let result = log_convolve(&skewed, &coin);
+    assert_eq!(result, log_convolve(&coin, &skewed));
+    for (x, y) in result.iter().zip([0.1, 0.5, 0.4]) {
+        approx::assert_relative_eq!(x.raw_prob(), y);
+    }
+    assert!(log_convolve(&coin, &[]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn concrete_log_sum_exp() -> Result<()> {
+    let x: Vec<LogProb<f64>> = (1..=100)
+        .map(|i| LogProb::from_raw_prob(f64::from(i) / 5050.0))
+        .collect::<Result<_, _>>()?;
+    approx::assert_relative_eq!(
+        log_sum_exp_f64(&x)?.into_inner(),
+        log_sum_exp(&x)?.into_inner(),
+        epsilon = 1e-14
+    );
+    let y: Vec<LogProb<f32>> = x
+        .iter()
+        .map(|x| LogProb::new(x.into_inner() as f32))
+        .collect::<Result<_, _>>()?;
+    approx::assert_relative_eq!(
+        log_sum_exp_f32(&y)?.into_inner(),
+        log_sum_exp(&y)?.into_inner(),
+        epsilon = 1e-6
+    );
+    let half = LogProb::from_raw_prob(0.5)?;
+    assert!(log_sum_exp_f64(&[half, half, half]).is_err());
+    assert_eq!(log_sum_exp_f32(&[]), Ok(LogProb::impossible()));
+    assert_eq!(
+        log_sum_exp_f32(&[LogProb::impossible(); 2]),
+        Ok(LogProb::impossible())
+    );
+    Ok(())
+}
+
+#[test]
+fn opposite_probs_buffer() -> Result<()> {
+    let x = [0.0, 0.1, 0.5, 0.999, 1.0]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let mut out = vec![LogProb::certain(); 10];
+    opposite_probs_into(&x, &mut out);
+    assert_eq!(out, x.iter().map(|x| x.opposite_prob()).collect::<Vec<_>>());
+    let capacity = out.capacity();
+    opposite_probs_into(&x[..2], &mut out);
+    assert_eq!(out.len(), 2);
+    assert_eq!(out.capacity(), capacity);
+    assert_eq!(out[0], LogProb::certain());
+    Ok(())
+}
+
+#[test]
+fn equality_matrix() -> Result<()> {
+    use std::cmp::Ordering;
+    let values = [0.0, -0.0, -1e-300, -0.5, -1e300, f64::NEG_INFINITY];
+    // Positions in `values` which should compare equal.
+    let class = [0, 0, 1, 2, 3, 4];
+    for (i, &a) in values.iter().enumerate() {
+        for (j, &b) in values.iter().enumerate() {
+            let (x, y) = (LogProb::new(a)?, LogProb::new(b)?);
+            assert_eq!(x == y, class[i] == class[j], "{a} == {b}");
+            assert_eq!(x.cmp(&y) == Ordering::Equal, class[i] == class[j]);
+            assert_eq!(x.partial_cmp(&y), Some(x.cmp(&y)));
+            assert_eq!(x.cmp(&y), class[j].cmp(&class[i]), "{a} cmp {b}");
+        }
+    }
+
+    let f32_values = [0.0_f32, -0.0, f32::NEG_INFINITY];
+    assert_eq!(LogProb::new(f32_values[0])?, LogProb::new(f32_values[1])?);
+    assert_ne!(LogProb::new(f32_values[0])?, LogProb::new(f32_values[2])?);
+    assert_eq!(LogProb::new(f32_values[2])?, LogProb::impossible());
+
+    let mut dedup = [0.0, -0.0, f64::NEG_INFINITY, f64::NEG_INFINITY, -1.0, -1.0]
+        .map(|x| LogProb::new(x).unwrap())
+        .to_vec();
+    dedup.dedup();
+    assert_eq!(dedup.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn ragged_log_sum_exp() -> Result<()> {
+    let p = |x: f64| LogProb::from_raw_prob(x).unwrap();
+    let groups = vec![
+        vec![p(0.1), p(0.2), p(0.3)],
+        vec![],
+        vec![p(0.9)],
+        vec![p(0.6), p(0.7)],
+        vec![p(0.0), p(0.5), p(0.0), p(0.25)],
+    ];
+    let sums = log_sum_exp_groups(&groups);
+    assert_eq!(sums.len(), groups.len());
+    for (sum, group) in sums.iter().zip(&groups) {
+        match (sum, log_sum_exp(group)) {
+            (Ok(x), Ok(y)) => approx::assert_relative_eq!(x.into_inner(), y.into_inner()),
+            (x, y) => assert_eq!(*x, y),
+        }
+    }
+    assert_eq!(sums[3], Err(ProbabilitiesSumToGreaterThanOne));
+    assert!(log_sum_exp_groups::<f32>(&[]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn tempering() -> Result<()> {
+    let x = [0.05, 0.15, 0.8, 0.0]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let mut previous_max = 1.0;
+    for temperature in [1.0, 2.0, 5.0, 50.0, 1e6] {
+        let y = temper(&x, temperature)?;
+        approx::assert_abs_diff_eq!(y.iter().log_sum_exp_float_no_alloc(), 0.0, epsilon = 1e-12);
+        assert_eq!(y[3], LogProb::impossible());
+        let max = y[2].raw_prob();
+        assert!(max <= previous_max);
+        previous_max = max;
+    }
+    approx::assert_relative_eq!(previous_max, 1.0 / 3.0, epsilon = 1e-5);
+    let uniform = temper(&x, f64::INFINITY)?;
+    assert_eq!(uniform[0], uniform[1]);
+    approx::assert_relative_eq!(uniform[2].raw_prob(), 1.0 / 3.0);
+
+    let same = temper(&x, 1.0)?;
+    for (a, b) in same.iter().zip(&x) {
+        approx::assert_abs_diff_eq!(a.raw_prob(), b.raw_prob(), epsilon = 1e-12);
+    }
+    let sharp = temper(&x, 0.01)?;
+    approx::assert_relative_eq!(sharp[2].raw_prob(), 1.0);
+
+    assert_eq!(temper(&x, 0.0), Err(InvalidTemperature));
+    assert_eq!(temper(&x, -1.0), Err(InvalidTemperature));
+    assert_eq!(temper(&x, f64::NAN), Err(InvalidTemperature));
+    Ok(())
+}
+
+#[test]
+fn lazy_log_sum_exp() -> Result<()> {
+    let x: Vec<LogProb<f64>> = (1..=20)
+        .map(|i| LogProb::from_raw_prob(f64::from(i) / 420.0))
+        .collect::<Result<_, _>>()?;
+    let mut calls = 0;
+    let lazy = log_sum_exp_fn(x.len(), |i| {
+        calls += 1;
+        x[i]
+    })?;
+    approx::assert_relative_eq!(lazy.into_inner(), log_sum_exp(&x)?.into_inner());
+    assert_eq!(calls, 2 * x.len());
+
+    assert_eq!(
+        log_sum_exp_fn(0, |_| LogProb::<f32>::certain()),
+        Ok(LogProb::impossible())
+    );
+    assert_eq!(
+        log_sum_exp_fn(3, |_| LogProb::<f32>::impossible()),
+        Ok(LogProb::impossible())
+    );
+    assert!(log_sum_exp_fn(3, |_| LogProb::from_raw_prob(0.5_f32).unwrap()).is_err());
+    Ok(())
+}
+
+#[test]
+fn narrowing_to_f32() -> Result<()> {
+    let x =
+        [-0.5, f64::NEG_INFINITY, -1e39, -3e38, 0.0, -f64::MAX].map(|x| LogProb::new(x).unwrap());
+    let (y, lost) = narrow_to_f32(&x);
+    assert_eq!(y.len(), x.len());
+    assert_eq!(y[0], LogProb::new(-0.5_f32)?);
+    assert_eq!(y[1], LogProb::impossible());
+    assert_eq!(y[2], LogProb::impossible());
+    assert_eq!(y[3], LogProb::new(-3e38_f32)?);
+    assert_eq!(y[4], LogProb::certain());
+    assert_eq!(lost, vec![2, 5]);
+    let (y, lost) = narrow_to_f32(&[]);
+    assert!(y.is_empty() && lost.is_empty());
+    Ok(())
+}
+
+#[test]
+fn dedup_near_equal() -> Result<()> {
+    let x = [
+        -0.5,
+        -0.5,
+        -0.5001,
+        -1.0,
+        -1.05,
+        -1.09,
+        -1.15,
+        -3.0,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ]
+    .map(|x| LogProb::new(x).unwrap());
+    let y: Vec<_> = x.iter().dedup_by_prob(0.1).collect();
+    assert_eq!(y, vec![x[0], x[3], x[6], x[7], x[8]]);
+    let z: Vec<_> = x.iter().dedup_by_prob(0.0).collect();
+    assert_eq!(z.len(), 8);
+    assert_eq!(
+        Vec::<LogProb<f32>>::new()
+            .into_iter()
+            .dedup_by_prob(0.1)
+            .count(),
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn mass_between_thresholds() -> Result<()> {
+    let x = [0.02, 0.08, 0.1, 0.15, 0.25, 0.4, 0.0]
+        .into_iter()
+        .map(LogProb::from_raw_prob)
+        .collect::<Result<Vec<LogProb<f64>>, _>>()?;
+    let lo = x[2];
+    let hi = x[4];
+    let direct = log_sum_exp(&x[2..=4])?;
+    approx::assert_relative_eq!(mass_in_range(&x, lo, hi).into_inner(), direct.into_inner());
+    approx::assert_relative_eq!(
+        mass_in_range(&x, LogProb::impossible(), LogProb::certain()).raw_prob(),
+        1.0
+    );
+    assert_eq!(
+        mass_in_range(&x, LogProb::from_raw_prob(0.5)?, LogProb::certain()),
+        LogProb::impossible()
+    );
+    assert_eq!(mass_in_range(&x, hi, lo), LogProb::impossible());
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_error_estimate() -> Result<()> {
+    let well_conditioned = [0.3, 0.2, 0.4].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let (sum, small_error) = well_conditioned.iter().log_sum_exp_with_error();
+    approx::assert_abs_diff_eq!(sum?.into_inner(), 0.9_f64.ln(), epsilon = small_error);
+
+    let n = 10_000;
+    let ill_conditioned: Vec<LogProb<f32>> = (0..n)
+        .map(|i| LogProb::new(-(i as f32) * 0.01 - 10.0))
+        .collect::<Result<_, _>>()?;
+    let reference = ill_conditioned
+        .iter()
+        .map(|x| f64::from(x.into_inner()))
+        .map(f64::exp)
+        .sum::<f64>()
+        .ln();
+    let (sum, large_error) = ill_conditioned.iter().log_sum_exp_with_error();
+    assert!(large_error > f32::EPSILON * n as f32);
+    assert!((f64::from(sum?.into_inner()) - reference).abs() <= f64::from(large_error));
+
+    let (_, f64_small_error) = [0.5, 0.5]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .iter()
+        .log_sum_exp_with_error();
+    assert!(f64_small_error < small_error);
+    let (sum, error) = Vec::<LogProb<f64>>::new().iter().log_sum_exp_with_error();
+    assert_eq!((sum?, error), (LogProb::impossible(), 0.0));
+    Ok(())
+}
+
+#[test]
+fn logits_to_distribution() -> Result<()> {
+    assert_eq!(
+        from_logits(&[0.0, 0.0])?,
+        vec![LogProb::from_raw_prob(0.5)?; 2]
+    );
+    let logits = [1.0_f32, -2.0, 3.5, f32::NEG_INFINITY];
+    let x = from_logits(&logits)?;
+    assert_eq!(x, softmax(&logits)?.collect::<Vec<_>>());
+    assert_eq!(x[3], LogProb::impossible());
+    approx::assert_abs_diff_eq!(x.iter().log_sum_exp_float_no_alloc(), 0.0, epsilon = 1e-6);
+    assert!(from_logits::<f64>(&[]).unwrap().is_empty());
+    assert_eq!(
+        from_logits(&[f32::NEG_INFINITY; 2])?,
+        vec![LogProb::from_raw_prob(0.5)?; 2]
+    );
+    assert_eq!(from_logits(&[f64::NEG_INFINITY])?, vec![LogProb::certain()]);
+    assert_eq!(
+        from_logits(&[0.0, f64::NAN]),
+        Err(FloatIsNanOrPositiveInfinity)
+    );
+    assert_eq!(
+        from_logits(&[0.0, f64::INFINITY]),
+        Err(FloatIsNanOrPositiveInfinity)
+    );
+    Ok(())
+}
+
+#[test]
+fn argmax_of_logits() -> Result<()> {
+    let logits = [0.3, -1.0, 4.2, 0.0, 4.1];
+    assert_eq!(argmax_logit(&logits), Some(2));
+    let probs = from_logits(&logits)?;
+    assert_eq!(argsort_desc(&probs)[0], 2);
+
+    assert_eq!(argmax_logit(&[1.0_f32, 3.0, 3.0, 2.0]), Some(1));
+    assert_eq!(argmax_logit(&[f64::NEG_INFINITY; 3]), Some(0));
+    assert_eq!(argmax_logit(&[-0.0, 0.0]), Some(1));
+    assert_eq!(argmax_logit(&[7.0_f64]), Some(0));
+    assert_eq!(argmax_logit::<f64>(&[]), None);
+    Ok(())
+}
+
+#[test]
+fn top_confidence() -> Result<()> {
+    let x = [0.1, 0.6, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let (i, p, margin) = confidence(&x).unwrap();
+    assert_eq!(i, 1);
+    assert_eq!(p, LogProb::from_raw_prob(0.6)?);
+    approx::assert_relative_eq!(margin, 2.0_f64.ln());
+
+    let tie = [0.4, 0.2, 0.4].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(confidence(&tie), Some((0, tie[0], 0.0)));
+    let single = [LogProb::new(-1.0_f32)?];
+    assert_eq!(confidence(&single), Some((0, single[0], f32::INFINITY)));
+    let impossible = [LogProb::new(-1.0)?, LogProb::impossible()];
+    assert_eq!(
+        confidence(&impossible),
+        Some((0, impossible[0], f64::INFINITY))
+    );
+    assert_eq!(confidence::<f64>(&[]), None);
+    Ok(())
+}
+
+#[test]
+fn seeded_log_sum_exp() -> Result<()> {
+    let x: Vec<LogProb<f64>> = (1..=30)
+        .map(|i| LogProb::from_raw_prob(f64::from(i) / 500.0))
+        .collect::<Result<_, _>>()?;
+    let (first, rest) = x.split_at(12);
+    let partial = first.iter().log_sum_exp()?;
+    let total = rest.iter().log_sum_exp_with_seed(partial)?;
+    approx::assert_relative_eq!(total.into_inner(), x.iter().log_sum_exp()?.into_inner());
+
+    let seed = LogProb::from_raw_prob(0.5)?;
+    assert_eq!(
+        Vec::<LogProb<f64>>::new()
+            .iter()
+            .log_sum_exp_with_seed(seed)?,
+        seed
+    );
+    assert_eq!(
+        x.iter().log_sum_exp_with_seed(LogProb::impossible())?,
+        x.iter().log_sum_exp()?
+    );
+    assert!([seed]
+        .iter()
+        .log_sum_exp_with_seed(LogProb::from_raw_prob(0.6)?)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn categorical_from_vec() -> Result<()> {
+    let dist = Categorical::try_from(vec![0.1, 0.2, 0.3, 0.4])?;
+    assert_eq!(dist.len(), 4);
+    approx::assert_relative_eq!(dist.probs()[3].raw_prob(), 0.4);
+    let json: Vec<f32> = serde_json::from_str("[0.5, 0.25, 0.25, 0.0]")?;
+    let dist = Categorical::try_from(json)?;
+    assert_eq!(dist.probs()[3], LogProb::impossible());
+    assert_eq!(Categorical::new(dist.clone().into_inner()), Ok(dist));
+
+    assert_eq!(
+        Categorical::try_from(vec![0.5, f64::NAN, 0.5]),
+        Err(CategoricalError::InvalidProbability(1))
+    );
+    assert_eq!(
+        Categorical::try_from(vec![0.5, 0.5, -0.1, 1.1]),
+        Err(CategoricalError::InvalidProbability(2))
+    );
+    assert_eq!(
+        Categorical::try_from(vec![0.5, 0.6]),
+        Err(CategoricalError::NotNormalized)
+    );
+    assert_eq!(
+        Categorical::<f64>::try_from(vec![]),
+        Err(CategoricalError::NotNormalized)
+    );
+    Ok(())
+}
+
+#[test]
+fn softmax_jacobian_vector_product() -> Result<()> {
+    let logits = [0.3, -1.2, 2.0, 0.5];
+    let upstream = [1.5, -0.5, 0.25, 2.0];
+    let probs = from_logits(&logits)?;
+    let jvp = softmax_jvp(&probs, &upstream)?;
+
+    // Finite differences of f(logits) = Σ v_i softmax(logits)_i.
+    let f = |logits: &[f64]| -> f64 {
+        from_logits(logits)
+            .unwrap()
+            .iter()
+            .zip(&upstream)
+            .map(|(p, v)| p.raw_prob() * v)
+            .sum()
+    };
+    let h = 1e-6;
+    for i in 0..logits.len() {
+        let mut plus = logits;
+        let mut minus = logits;
+        plus[i] += h;
+        minus[i] -= h;
+        let numeric = (f(&plus) - f(&minus)) / (2.0 * h);
+        approx::assert_abs_diff_eq!(jvp[i], numeric, epsilon = 1e-8);
+    }
+    approx::assert_abs_diff_eq!(jvp.iter().sum::<f64>(), 0.0, epsilon = 1e-12);
+    assert_eq!(softmax_jvp(&probs, &upstream[1..]), Err(LengthMismatch));
+    Ok(())
+}
+
+#[test]
+fn cross_entropy_gradient() -> Result<()> {
+    let probs = from_logits(&[1.0, 2.0, 0.5])?;
+    let grad = cross_entropy_logit_grad(&probs, 1);
+    approx::assert_abs_diff_eq!(grad.iter().sum::<f64>(), 0.0, epsilon = 1e-12);
+    for (i, (g, p)) in grad.iter().zip(&probs).enumerate() {
+        let one_hot = if i == 1 { 1.0 } else { 0.0 };
+        approx::assert_relative_eq!(*g, p.raw_prob() - one_hot);
+    }
+    assert!(grad[1] < 0.0 && grad[0] > 0.0 && grad[2] > 0.0);
+
+    // Finite differences of the negative log-likelihood of the target.
+    let nll = |logits: &[f64]| -from_logits(logits).unwrap()[1].into_inner();
+    let h = 1e-6;
+    let logits = [1.0, 2.0, 0.5];
+    for i in 0..3 {
+        let (mut plus, mut minus) = (logits, logits);
+        plus[i] += h;
+        minus[i] -= h;
+        approx::assert_abs_diff_eq!(
+            grad[i],
+            (nll(&plus) - nll(&minus)) / (2.0 * h),
+            epsilon = 1e-8
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "target is out of bounds")]
+fn cross_entropy_gradient_out_of_bounds() {
+    let probs = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+    cross_entropy_logit_grad(&probs, 2);
+}
+
+#[test]
+fn label_smoothing() -> Result<()> {
+    let probs = [0.7, 0.2, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(label_smoothed_nll(&probs, 0, 0.0), -(0.7_f64.ln()));
+    approx::assert_relative_eq!(label_smoothed_nll(&probs, 1, 0.0), -(0.2_f64.ln()));
+
+    let mean = -(0.7_f64.ln() + 0.2_f64.ln() + 0.1_f64.ln()) / 3.0;
+    approx::assert_relative_eq!(
+        label_smoothed_nll(&probs, 0, 0.1),
+        0.9 * -(0.7_f64.ln()) + 0.1 * mean
+    );
+    approx::assert_relative_eq!(label_smoothed_nll(&probs, 2, 1.0), mean);
+
+    let probs = [1.0, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(label_smoothed_nll(&probs, 0, 0.0), 0.0);
+    assert_eq!(label_smoothed_nll(&probs, 0, 0.1), f64::INFINITY);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "smoothing must be in [0, 1]")]
+fn label_smoothing_invalid() {
+    let probs = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+    label_smoothed_nll(&probs, 0, 1.5);
+}
+
+#[test]
+fn top_k_masking() -> Result<()> {
+    let v = [0.1, 0.3, 0.05, 0.3, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(top_k_mask(&v, 2), vec![false, true, false, true, false]);
+    assert_eq!(top_k_mask(&v, 1), vec![false, true, false, false, false]);
+    assert_eq!(top_k_mask(&v, 3), vec![false, true, false, true, true]);
+    assert_eq!(top_k_mask(&v, 0), vec![false; 5]);
+    assert_eq!(top_k_mask(&v, 10), vec![true; 5]);
+
+    let tied = [LogProb::from_raw_prob(0.25_f64).unwrap(); 4];
+    assert_eq!(top_k_mask(&tied, 2), vec![true, true, false, false]);
+    assert!(top_k_mask::<f64>(&[], 3).is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn tempered_sampling() -> Result<()> {
+    use logprob::sample_tempered;
+    use rand::SeedableRng;
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    let v = [0.1, 0.6, 0.0, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(sample_tempered(&v, 0.0, &mut rng), Some(1));
+    assert_eq!(sample_tempered::<f64, _>(&[], 1.0, &mut rng), None);
+    assert_eq!(
+        sample_tempered(&[LogProb::<f64>::impossible(); 3], 1.0, &mut rng),
+        None
+    );
+
+    let n = 20_000;
+    let mut frequencies = |temperature: f64| {
+        let mut counts = [0usize; 4];
+        for _ in 0..n {
+            counts[sample_tempered(&v, temperature, &mut rng).unwrap()] += 1;
+        }
+        counts.map(|c| c as f64 / n as f64)
+    };
+    let freq = frequencies(1.0);
+    assert_eq!(freq[2], 0.0);
+    for (f, p) in freq.iter().zip(&v) {
+        approx::assert_abs_diff_eq!(*f, p.raw_prob(), epsilon = 0.02);
+    }
+    let hot = frequencies(5.0);
+    let cold = frequencies(0.5);
+    assert_eq!(hot[2], 0.0);
+    assert!(hot[1] < freq[1] && freq[1] < cold[1]);
+    assert!(hot[0] > freq[0] && freq[0] > cold[0]);
+    Ok(())
+}
+
+#[test]
+fn approximate_ordering() -> Result<()> {
+    let approx = |x: f64| LogProbApprox::new(LogProb::new(x).unwrap(), 0.1);
+    assert_eq!(approx(-1.01), approx(-1.09));
+    assert_eq!(approx(0.0), approx(-0.0));
+    assert_eq!(approx(0.0), approx(-0.05));
+    assert_eq!(approx(f64::NEG_INFINITY), approx(f64::NEG_INFINITY));
+    assert!(approx(-1.11) < approx(-1.09));
+    assert!(approx(f64::NEG_INFINITY) < approx(-1000.0));
+
+    let mut x: Vec<_> = [-1.02, -0.31, -1.05, -0.35, -1.01, f64::NEG_INFINITY, -0.39]
+        .into_iter()
+        .map(approx)
+        .collect();
+    x.sort();
+    let x: Vec<f64> = x.iter().map(|x| x.value().into_inner()).collect();
+    assert_eq!(
+        x,
+        vec![f64::NEG_INFINITY, -1.02, -1.05, -1.01, -0.31, -0.35, -0.39]
+    );
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "cannot compare values with different tolerances")]
+fn approximate_ordering_mixed_tolerances() {
+    let mut x = [(-0.5, 1.0), (-1.5, 10.0), (-2.5, 1.0)]
+        .map(|(x, tol)| LogProbApprox::new(LogProb::new(x).unwrap(), tol));
+    x.sort();
+}
+
+#[test]
+fn zipped_log_products() -> Result<()> {
+    let x = [0.5, 0.2, 0.0, 1.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let y = [0.4, 0.5, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let z: Vec<_> = x.iter().zip_log_mul(y).collect();
+    assert_eq!(z.len(), 3);
+    approx::assert_relative_eq!(z[0].raw_prob(), 0.2);
+    approx::assert_relative_eq!(z[1].raw_prob(), 0.1);
+    assert_eq!(z[2], LogProb::impossible());
+
+    let total = x.into_iter().zip_log_mul(y).log_sum_exp()?;
+    approx::assert_relative_eq!(total.raw_prob(), 0.3);
+    Ok(())
+}
+
+#[test]
+fn generalized_entropies() -> Result<()> {
+    let raw: [f64; 5] = [0.5, 0.25, 0.125, 0.125, 0.0];
+    let p = raw.map(|x| LogProb::from_raw_prob(x).unwrap());
+    let support = &raw[..4];
+    let shannon: f64 = support.iter().map(|p| -p * p.ln()).sum();
+    let power_sum = |a: f64| support.iter().map(|p| p.powf(a)).sum::<f64>();
+
+    approx::assert_relative_eq!(generalized_entropy(&p, EntropyKind::Shannon), shannon);
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Shannon),
+        1.75 * 2.0_f64.ln()
+    );
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Renyi(0.5)),
+        power_sum(0.5).ln() / 0.5
+    );
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Renyi(0.0)),
+        4.0_f64.ln()
+    );
+    approx::assert_relative_eq!(generalized_entropy(&p, EntropyKind::Renyi(1.0)), shannon);
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Renyi(f64::INFINITY)),
+        2.0_f64.ln()
+    );
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Tsallis(2.0)),
+        1.0 - power_sum(2.0)
+    );
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Tsallis(0.5)),
+        (1.0 - power_sum(0.5)) / -0.5
+    );
+    approx::assert_relative_eq!(generalized_entropy(&p, EntropyKind::Tsallis(1.0)), shannon);
+    approx::assert_relative_eq!(generalized_entropy(&p, EntropyKind::Min), 2.0_f64.ln());
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Collision),
+        -power_sum(2.0).ln()
+    );
+    approx::assert_relative_eq!(
+        generalized_entropy(&p, EntropyKind::Collision),
+        generalized_entropy(&p, EntropyKind::Renyi(2.0))
+    );
+
+    let certain = [LogProb::new(0.0_f64)?, LogProb::impossible()];
+    for kind in [
+        EntropyKind::Shannon,
+        EntropyKind::Renyi(2.0),
+        EntropyKind::Renyi(f64::INFINITY),
+        EntropyKind::Tsallis(2.0),
+        EntropyKind::Min,
+        EntropyKind::Collision,
+    ] {
+        assert_eq!(generalized_entropy(&certain, kind), 0.0);
+        assert_eq!(generalized_entropy::<f64>(&[], kind), 0.0);
+    }
+    Ok(())
+}
+
+#[test]
+fn exact_size_log_sum_exp() -> Result<()> {
+    let x: Vec<LogProb<f64>> = (1..=1000)
+        .map(|i| LogProb::from_raw_prob(f64::from(i) / 1_000_000.0))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(x.iter().log_sum_exp_exact()?, x.iter().log_sum_exp()?);
+    approx::assert_relative_eq!(x.iter().log_sum_exp_exact()?.raw_prob(), 0.5005);
+    assert_eq!(
+        std::iter::empty::<LogProb<f64>>().log_sum_exp_exact()?,
+        LogProb::impossible()
+    );
+    let too_big = [LogProb::new(-0.1_f64)?; 2];
+    assert!(too_big.iter().log_sum_exp_exact().is_err());
+    Ok(())
+}
+
+#[test]
+fn joint_probability_of_sequence() -> Result<()> {
+    let half = LogProb::from_raw_prob(0.5_f64)?;
+    assert_eq!(joint_log_prob(&[half, half]), LogProb::from_raw_prob(0.25)?);
+    assert_eq!(joint_log_prob::<f64>(&[]), LogProb::certain());
+    assert_eq!(
+        joint_log_prob(&[half, LogProb::impossible(), half]),
+        LogProb::impossible()
+    );
+    let x = [0.9, 0.5, 0.2].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(joint_log_prob(&x).raw_prob(), 0.09);
+    Ok(())
+}
+
+#[test]
+fn sequence_negative_log_likelihood() -> Result<()> {
+    let raw: [f64; 4] = [0.5, 0.1, 1.0, 0.25];
+    let x = raw.map(|x| LogProb::from_raw_prob(x).unwrap());
+    let (per_token, total) = sequence_nll(&x);
+    assert_eq!(per_token.len(), 4);
+    for (s, p) in per_token.iter().zip(raw) {
+        approx::assert_relative_eq!(*s, -p.ln());
+    }
+    assert_eq!(per_token[2], 0.0);
+    approx::assert_relative_eq!(total, -(0.5_f64 * 0.1 * 0.25).ln());
+    approx::assert_relative_eq!(total, -joint_log_prob(&x).into_inner());
+
+    let (per_token, total) = sequence_nll::<f64>(&[]);
+    assert!(per_token.is_empty());
+    assert_eq!(total, 0.0);
+
+    let (_, total) = sequence_nll(&[LogProb::impossible(), x[0]]);
+    assert_eq!(total, f64::INFINITY);
+    Ok(())
+}
+
+#[test]
+fn rolling_perplexity() -> Result<()> {
+    let raw: [f64; 7] = [0.5, 0.1, 0.9, 0.3, 0.05, 0.7, 0.2];
+    let x = raw.map(|x| LogProb::from_raw_prob(x).unwrap());
+    let batch = |tokens: &[LogProb<f64>]| {
+        let (_, total) = sequence_nll(tokens);
+        (total / tokens.len() as f64).exp()
+    };
+
+    let mut ppl = RollingPerplexity::<f64>::new(3);
+    assert!(ppl.perplexity().is_nan());
+    for (i, &lp) in x.iter().enumerate() {
+        ppl.push(lp);
+        let window = &x[(i + 1).saturating_sub(3)..=i];
+        approx::assert_relative_eq!(ppl.perplexity(), batch(window), max_relative = 1e-12);
+    }
+
+    let mut ppl = RollingPerplexity::new(2);
+    ppl.push(x[0]);
+    ppl.push(LogProb::impossible());
+    assert_eq!(ppl.perplexity(), f64::INFINITY);
+    ppl.push(x[1]);
+    assert_eq!(ppl.perplexity(), f64::INFINITY);
+    ppl.push(x[2]);
+    approx::assert_relative_eq!(ppl.perplexity(), batch(&x[1..3]));
+    Ok(())
+}
+
+#[test]
+fn categorical_iteration() -> Result<()> {
+    let dist = Categorical::try_from(vec![0.2_f64, 0.3, 0.5])?;
+    let borrowed: Vec<LogProb<f64>> = (&dist).into_iter().copied().collect();
+    assert_eq!(borrowed, dist.probs());
+    assert_eq!(dist.iter().count(), 3);
+
+    let round_trip: Categorical<f64> = dist.clone().into_iter().collect();
+    assert_eq!(round_trip.len(), 3);
+    for (a, b) in round_trip.iter().zip(&dist) {
+        approx::assert_relative_eq!(a.raw_prob(), b.raw_prob(), max_relative = 1e-12);
+    }
+
+    let half = LogProb::from_raw_prob(0.5)?;
+    let scaled: Categorical<f64> = dist.into_iter().map(|x| x + half).collect();
+    approx::assert_relative_eq!(scaled.probs()[2].raw_prob(), 0.5);
+
+    let filtered: Categorical<f64> = [0.5, 0.3, 0.2]
+        .map(|x| LogProb::from_raw_prob(x).unwrap())
+        .into_iter()
+        .filter(|x| x.raw_prob() > 0.25)
+        .collect();
+    approx::assert_relative_eq!(filtered.probs()[0].raw_prob(), 0.625);
+    approx::assert_relative_eq!(filtered.probs()[1].raw_prob(), 0.375);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "cannot renormalize a distribution with no possible elements")]
+fn categorical_from_impossible_iterator() {
+    let _: Categorical<f64> = [LogProb::impossible(); 2].into_iter().collect();
+}
+
+#[test]
+fn log_sum_exp_all_impossible() -> Result<()> {
+    let x = [LogProb::<f64>::impossible(); 2];
+    assert_eq!(log_sum_exp(&x)?, LogProb::impossible());
+    assert_eq!(log_sum_exp_clamped(&x), LogProb::impossible());
+    assert_eq!(log_sum_exp_float(&x), f64::NEG_INFINITY);
+    assert_eq!(x.iter().log_sum_exp()?, LogProb::impossible());
+    assert_eq!(x.iter().log_sum_exp_exact()?, LogProb::impossible());
+    assert_eq!(x.iter().log_sum_exp_clamped(), LogProb::impossible());
+    assert_eq!(x.iter().log_sum_exp_float(), f64::NEG_INFINITY);
+    assert_eq!(x.iter().log_sum_exp_no_alloc()?, LogProb::impossible());
+    Ok(())
+}
+
+#[test]
+fn clamped_addition_with_impossible() -> Result<()> {
+    for p in [0.5, 1e-300, 0.999, 1.0] {
+        let x = LogProb::from_raw_prob(p)?;
+        assert_eq!(LogProb::impossible().add_log_prob_clamped(x), x);
+        assert_eq!(x.add_log_prob_clamped(LogProb::impossible()), x);
+        assert_eq!(
+            LogProb::impossible().add_log_prob_clamped(x).into_inner(),
+            x.into_inner()
+        );
+        assert_eq!(LogProb::impossible().add_log_prob(x)?, x);
+        assert_eq!(LogProb::impossible().add_log_prob_float(x), x.into_inner());
+    }
+    assert_eq!(
+        LogProb::<f64>::impossible().add_log_prob_clamped(LogProb::impossible()),
+        LogProb::impossible()
+    );
+    let x = LogProb::new(-0.1_f64)?;
+    assert_eq!(x.add_log_prob_clamped(x), LogProb::certain());
+    Ok(())
+}
+
+#[test]
+fn weighted_medians() -> Result<()> {
+    let weights = [0.1, 0.2, 0.6, 0.1].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(weighted_median(&[4, 1, 3, 2], &weights), Ok(3));
+    assert_eq!(weighted_median(&[1.0, 2.0, 3.0, 4.0], &weights), Ok(3.0));
+
+    let uniform = [LogProb::from_raw_prob(0.25_f64)?; 4];
+    assert_eq!(weighted_median(&[10, 40, 20, 30], &uniform), Ok(20));
+
+    let weights = [0.0, 0.3, 0.0, 0.1, 0.6].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(
+        weighted_median(&['a', 'b', 'c', 'd', 'e'], &weights),
+        Ok('e')
+    );
+
+    let unnormalized = [LogProb::from_raw_prob(0.1_f64)?; 3];
+    assert_eq!(weighted_median(&[3, 1, 2], &unnormalized), Ok(2));
+
+    assert_eq!(weighted_median(&[1, 2], &uniform), Err(LengthMismatch));
+    Ok(())
+}
+
+#[test]
+fn gini_impurities() -> Result<()> {
+    for n in [1, 2, 3, 10, 1000] {
+        let x = vec![LogProb::from_raw_prob(1.0 / f64::from(n))?; n as usize];
+        approx::assert_relative_eq!(gini_impurity(&x), 1.0 - 1.0 / f64::from(n), epsilon = 1e-12);
+    }
+    let x = [0.5, 0.0, 0.3, 0.2].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(gini_impurity(&x), 1.0 - (0.25 + 0.09 + 0.04));
+    assert_eq!(gini_impurity(&[LogProb::<f64>::certain()]), 0.0);
+    assert_eq!(gini_impurity::<f64>(&[]), 0.0);
+    Ok(())
+}
+
+#[test]
+fn floored_raw_probs() -> Result<()> {
+    let v = from_raw_probs_floored(&[0.0_f64, 0.7, 0.3, 0.0], 0.05).unwrap();
+    assert!(v.iter().all(|x| x.into_inner().is_finite()));
+    approx::assert_relative_eq!(v.iter().log_sum_exp()?.raw_prob(), 1.0);
+    approx::assert_relative_eq!(v[0].raw_prob(), 0.05 / 1.1);
+    approx::assert_relative_eq!(v[1].raw_prob(), 0.7 / 1.1);
+    assert_eq!(v[0], v[3]);
+
+    let v = from_raw_probs_floored(&[0.0_f64; 4], 0.1).unwrap();
+    for x in v {
+        approx::assert_relative_eq!(x.raw_prob(), 0.25);
+    }
+    assert_eq!(
+        from_raw_probs_floored(&[0.5, 1.5], 0.1),
+        Err(FloorError::InvalidProbability(1))
+    );
+    for floor in [0.0, -0.1, 1.5, f64::NAN] {
+        assert_eq!(
+            from_raw_probs_floored(&[0.5, 0.5], floor),
+            Err(FloorError::InvalidFloor)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_nonempty() -> Result<()> {
+    assert_eq!(
+        std::iter::empty::<LogProb<f64>>().log_sum_exp_nonempty(),
+        None
+    );
+    assert_eq!(
+        [LogProb::<f64>::impossible()].iter().log_sum_exp_nonempty(),
+        Some(Ok(LogProb::impossible()))
+    );
+    let x = [0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(
+        x.iter().log_sum_exp_nonempty(),
+        Some(Ok(LogProb::from_raw_prob(0.75)?))
+    );
+    let too_big = [LogProb::new(-0.1_f64)?; 2];
+    assert_eq!(
+        too_big.into_iter().log_sum_exp_nonempty(),
+        Some(Err(ProbabilitiesSumToGreaterThanOne))
+    );
+    Ok(())
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn weighted_reservoir_sampling() -> Result<()> {
+    use logprob::weighted_reservoir_sample;
+    use rand::SeedableRng;
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    let w = [0.1, 0.0, 0.6, 0.3].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert!(weighted_reservoir_sample(w.into_iter(), 0, &mut rng).is_empty());
+    let mut all = weighted_reservoir_sample(w.into_iter(), 10, &mut rng);
+    all.sort();
+    assert_eq!(all, vec![0, 2, 3]);
+
+    // The item with the largest key is a draw from the normalized weights.
+    let n = 20_000;
+    let mut first = [0usize; 4];
+    let mut included = [0usize; 4];
+    for _ in 0..n {
+        let sample = weighted_reservoir_sample(w.into_iter(), 2, &mut rng);
+        assert_eq!(sample.len(), 2);
+        assert_ne!(sample[0], sample[1]);
+        first[sample[0]] += 1;
+        for i in sample {
+            included[i] += 1;
+        }
+    }
+    for (count, p) in first.iter().zip(&w) {
+        approx::assert_abs_diff_eq!(*count as f64 / n as f64, p.raw_prob(), epsilon = 0.02);
+    }
+    // P(i included) = p_i + Σ_{j != i} p_j * p_i / (1 - p_j)
+    let p = [0.1, 0.0, 0.6, 0.3];
+    for i in 0..4 {
+        let expected = p[i]
+            + (0..4)
+                .filter(|&j| j != i)
+                .map(|j| p[j] * p[i] / (1.0 - p[j]))
+                .sum::<f64>();
+        approx::assert_abs_diff_eq!(included[i] as f64 / n as f64, expected, epsilon = 0.02);
+    }
+
+    // Tiny weights whose keys would underflow still follow their relative sizes.
+    let tiny = [-2000.0, -2000.0 - 3.0_f64.ln()].map(|x| LogProb::new(x).unwrap());
+    let wins = (0..n)
+        .filter(|_| weighted_reservoir_sample(tiny.into_iter(), 1, &mut rng) == vec![0])
+        .count();
+    approx::assert_abs_diff_eq!(wins as f64 / n as f64, 0.75, epsilon = 0.02);
+    Ok(())
+}
+
+#[test]
+fn parse_log_prob() -> Result<()> {
+    let accepted: [(&str, f64); 14] = [
+        ("-1.5", -1.5),
+        ("-1e-10", -1e-10),
+        ("-2.5E3", -2500.0),
+        ("-inf", f64::NEG_INFINITY),
+        ("-Inf", f64::NEG_INFINITY),
+        ("-INF", f64::NEG_INFINITY),
+        ("-infinity", f64::NEG_INFINITY),
+        ("-Infinity", f64::NEG_INFINITY),
+        ("0", 0.0),
+        ("+0", 0.0),
+        ("-0", -0.0),
+        ("-0.0e5", -0.0),
+        ("  -3 ", -3.0),
+        ("-.5", -0.5),
+    ];
+    for (s, expected) in accepted {
+        let x: LogProb<f64> = s.parse()?;
+        assert_eq!(x.into_inner(), expected, "{s}");
+    }
+    assert!("-0"
+        .parse::<LogProb<f64>>()?
+        .into_inner()
+        .is_sign_negative());
+    assert!("+0"
+        .parse::<LogProb<f64>>()?
+        .into_inner()
+        .is_sign_positive());
+    assert_eq!("-0.25".parse::<LogProb<f32>>()?, LogProb::new(-0.25_f32)?);
+
+    let rejected = [
+        ("1e-10", ParseLogProbError::NanOrPositive),
+        ("0.5", ParseLogProbError::NanOrPositive),
+        ("inf", ParseLogProbError::NanOrPositive),
+        ("+Infinity", ParseLogProbError::NanOrPositive),
+        ("NaN", ParseLogProbError::NanOrPositive),
+        ("nan", ParseLogProbError::NanOrPositive),
+        ("", ParseLogProbError::NotAFloat),
+        ("-", ParseLogProbError::NotAFloat),
+        ("abc", ParseLogProbError::NotAFloat),
+        ("-1.0.0", ParseLogProbError::NotAFloat),
+        ("--1", ParseLogProbError::NotAFloat),
+    ];
+    for (s, err) in rejected {
+        assert_eq!(s.parse::<LogProb<f64>>(), Err(err), "{s}");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn fast_log_sum_exp_clamped() -> Result<()> {
+    use logprob::log_sum_exp_clamped_f32;
+    use rand::prelude::*;
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    for n in [1, 7, 8, 9, 100, 4099] {
+        for scale in [1.0, 1.0 / n as f32] {
+            let x: Vec<LogProb<f32>> = (0..n)
+                .map(|_| LogProb::from_raw_prob(rng.gen::<f32>() * scale).unwrap())
+                .collect();
+            let fast = log_sum_exp_clamped_f32(&x);
+            let slow = log_sum_exp_clamped(&x);
+            approx::assert_abs_diff_eq!(fast.into_inner(), slow.into_inner(), epsilon = 1e-5);
+            assert!(fast.into_inner() <= 0.0);
+        }
+    }
+    assert_eq!(log_sum_exp_clamped_f32(&[]), LogProb::impossible());
+    assert_eq!(
+        log_sum_exp_clamped_f32(&[LogProb::impossible(); 9]),
+        LogProb::impossible()
+    );
+    assert_eq!(
+        log_sum_exp_clamped_f32(&[LogProb::new(-0.1)?; 9]),
+        LogProb::certain()
+    );
+    Ok(())
+}
+
+#[test]
+fn split_sorted_distribution_at_mass() -> Result<()> {
+    let x = [0.5, 0.3, 0.15, 0.05].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(split_at_mass(&x, 0.9), 3);
+    assert_eq!(split_at_mass(&x, 0.5), 1);
+    assert_eq!(split_at_mass(&x, 0.6), 2);
+    assert_eq!(split_at_mass(&x, 0.99), 4);
+    assert_eq!(split_at_mass(&x, 0.0), 0);
+    assert_eq!(split_at_mass(&x, -1.0), 0);
+    assert_eq!(split_at_mass(&x[..2], 0.9), 2);
+    assert_eq!(split_at_mass::<f64>(&[], 0.5), 0);
+
+    let with_impossible = [0.7, 0.3, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(split_at_mass(&with_impossible, 0.95), 2);
+    Ok(())
+}
+
+#[test]
+fn markov_entropy_rate() -> Result<()> {
+    // A two-state chain which switches with probabilities a and b has the stationary
+    // distribution (b, a) / (a + b).
+    let (a, b) = (0.2_f64, 0.6_f64);
+    let h = |p: f64| -p * p.ln() - (1.0 - p) * (1.0 - p).ln();
+    let transitions = LogProbMatrix::new(
+        2,
+        2,
+        [1.0 - a, a, b, 1.0 - b]
+            .map(|x| LogProb::from_raw_prob(x).unwrap())
+            .to_vec(),
+    )?;
+    let stationary = [b / (a + b), a / (a + b)].map(|x| LogProb::from_raw_prob(x).unwrap());
+    approx::assert_relative_eq!(
+        entropy_rate(&stationary, &transitions)?,
+        (b * h(a) + a * h(b)) / (a + b)
+    );
+
+    // A deterministic cycle has no entropy, and impossible transitions are skipped.
+    let (one, zero) = (LogProb::certain(), LogProb::impossible());
+    let cycle = LogProbMatrix::new(2, 2, vec![zero, one, one, zero])?;
+    let half = LogProb::from_raw_prob(0.5)?;
+    assert_eq!(entropy_rate(&[half, half], &cycle)?, 0.0);
+
+    assert_eq!(entropy_rate(&[half], &cycle), Err(DimensionMismatch));
+    let rectangular = LogProbMatrix::new(1, 2, vec![half, half])?;
+    assert_eq!(entropy_rate(&[one], &rectangular), Err(DimensionMismatch));
+    Ok(())
+}
+
+#[test]
+fn checked_log_sum_exp() -> Result<()> {
+    let x = [0.25, 0.5].map(|x| LogProb::from_raw_prob(x).unwrap());
+    assert_eq!(log_sum_exp_checked(&x), Ok(LogProb::from_raw_prob(0.75)?));
+    assert_eq!(log_sum_exp_checked::<f64>(&[]), Ok(LogProb::impossible()));
+    assert_eq!(
+        log_sum_exp_checked(&[LogProb::<f64>::impossible(); 2]),
+        Ok(LogProb::impossible())
+    );
+    assert_eq!(
+        log_sum_exp_checked(&[LogProb::new(-0.1_f64)?; 2]),
+        Err(LogSumExpError::ProbabilitiesSumToGreaterThanOne)
+    );
+    assert_eq!(
+        log_sum_exp_checked(&[LogProb::new(-0.5_f64)?; 3]),
+        Err(LogSumExpError::ProbabilitiesSumToGreaterThanOne)
+    );
+    assert_eq!(
+        log_sum_exp_checked(&[LogProb::from_raw_prob(0.5_f64)?; 2]),
+        Ok(LogProb::certain())
+    );
+
+    // SAFETY: deliberately invalid, to check that log_sum_exp_checked rejects it.
+    let unchecked = unsafe { LogProb::new_unchecked(f64::NAN) };
+    assert!(unchecked.into_inner().is_nan());
+    assert_eq!(
+        log_sum_exp_checked(&[x[0], unchecked, x[1]]),
+        Err(LogSumExpError::InvalidInput)
+    );
+    assert_eq!(
+        log_sum_exp_checked(&[unchecked]),
+        Err(LogSumExpError::InvalidInput)
+    );
+    // SAFETY: -2.0 is negative.
+    assert_eq!(
+        unsafe { LogProb::new_unchecked(-2.0_f64) },
+        LogProb::new(-2.0_f64)?
+    );
+    Ok(())
+}
+
+#[test]
+fn take_while_mass_stream() -> Result<()> {
+    let x = [0.5, 0.3, 0.15, 0.05].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let head: Vec<_> = x.iter().take_while_mass(0.7).collect();
+    assert_eq!(head, x[..2].to_vec());
+    assert_eq!(x.iter().take_while_mass(0.5).count(), 1);
+    assert_eq!(x.iter().take_while_mass(0.9).count(), 3);
+    assert_eq!(x.iter().take_while_mass(0.99).count(), 4);
+    assert_eq!(x.iter().take_while_mass(0.0).count(), 0);
+    for p in [0.1, 0.5, 0.6, 0.81, 0.9, 0.96, 1.5] {
+        assert_eq!(x.iter().take_while_mass(p).count(), split_at_mass(&x, p));
+    }
+
+    // It stops pulling from the stream once the mass is reached.
+    let mut pulled = 0;
+    let stream = (0..).map(|_| {
+        pulled += 1;
+        LogProb::from_raw_prob(0.25_f64).unwrap()
+    });
+    assert_eq!(stream.take_while_mass(0.6).count(), 3);
+    assert_eq!(pulled, 3);
+    Ok(())
+}
+
+#[test]
+fn sum_and_product_of_log_probs() -> Result<()> {
+    let x = [0.5, 0.4, 0.25].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let expected = joint_log_prob(&x);
+
+    let owned_sum: LogProb<f64> = x.into_iter().sum();
+    let borrowed_sum: LogProb<f64> = x.iter().sum();
+    let owned_product: LogProb<f64> = x.into_iter().product();
+    let borrowed_product: LogProb<f64> = x.iter().product();
+    for p in [owned_sum, borrowed_sum, owned_product, borrowed_product] {
+        assert_eq!(p, expected);
+        approx::assert_relative_eq!(p.raw_prob(), 0.05);
+    }
+
+    let empty: LogProb<f64> = std::iter::empty::<&LogProb<f64>>().product();
+    assert_eq!(empty, LogProb::certain());
+    let with_impossible: LogProb<f32> = [LogProb::certain(), LogProb::impossible()].iter().sum();
+    assert_eq!(with_impossible, LogProb::impossible());
+    Ok(())
+}
+
+#[test]
+fn log_sum_exp_builder() -> Result<()> {
+    use rand::prelude::*;
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    let base = [
+        LogSumExpBuilder::new(),
+        LogSumExpBuilder::new().kahan(),
+        LogSumExpBuilder::new().pairwise(),
+        LogSumExpBuilder::new().pairwise().kahan(),
+    ];
+    let builders: Vec<_> = base
+        .into_iter()
+        .flat_map(|b| [b, b.simd(), b.parallel(), b.simd().parallel()])
+        .collect();
+    assert_eq!(
+        LogSumExpBuilder::new().parallel().pairwise(),
+        LogSumExpBuilder::new().pairwise().parallel()
+    );
+
+    for n in [1, 2, 15, 16, 17, 1000, 10_000] {
+        let x: Vec<LogProb<f64>> = (0..n)
+            .map(|_| LogProb::from_raw_prob(rng.gen::<f64>() / n as f64).unwrap())
+            .collect();
+        let expected = log_sum_exp(&x)?;
+        for builder in &builders {
+            let total = builder.reduce_slice(&x)?;
+            assert_eq!(builder.reduce(x.iter().copied())?, total);
+            approx::assert_relative_eq!(
+                total.into_inner(),
+                expected.into_inner(),
+                max_relative = 1e-12,
+                epsilon = 1e-14
+            );
+        }
+    }
+
+    let impossible = [LogProb::<f64>::impossible(); 20];
+    let too_big = [LogProb::new(-0.1_f64)?; 20];
+    for builder in &builders {
+        assert_eq!(
+            builder.reduce(std::iter::empty::<LogProb<f64>>())?,
+            LogProb::impossible()
+        );
+        assert_eq!(builder.reduce(impossible)?, LogProb::impossible());
+        assert_eq!(
+            builder.reduce(too_big),
+            Err(ProbabilitiesSumToGreaterThanOne)
+        );
+    }
+
+    let x: Vec<LogProb<f32>> = (0..10_000)
+        .map(|_| LogProb::from_raw_prob(rng.gen::<f32>() / 10_000.0).unwrap())
+        .collect();
+    let expected = log_sum_exp(&x)?;
+    for builder in &builders {
+        approx::assert_relative_eq!(
+            builder.reduce_slice(&x)?.into_inner(),
+            expected.into_inner(),
+            epsilon = 1e-5
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn from_percentages() -> Result<()> {
+    assert_eq!(
+        LogProb::from_percent(25.0_f64)?,
+        LogProb::from_raw_prob(0.25)?
+    );
+    assert_eq!(LogProb::from_percent(100.0_f64)?, LogProb::certain());
+    assert_eq!(LogProb::from_percent(0.0_f64)?, LogProb::impossible());
+    assert_eq!(
+        LogProb::from_percent(50.0_f32)?,
+        LogProb::from_raw_prob(0.5)?
+    );
+    for bad in [150.0, 100.000_000_000_001, -0.5, f64::NAN, f64::INFINITY] {
+        assert_eq!(LogProb::from_percent(bad), Err(FloatIsNanOrPositive));
+    }
+    Ok(())
+}
+
+#[test]
+fn detailed_log_sum_exp() -> Result<()> {
+    let x = [0.1, 0.4, 0.0, 0.2].map(|x| LogProb::from_raw_prob(x).unwrap());
+    let result: LogSumExpResult<f64> = x.iter().log_sum_exp_detailed();
+    approx::assert_relative_eq!(result.value().raw_prob(), 0.7);
+    assert_eq!(result.value(), x.iter().log_sum_exp()?);
+    assert_eq!(result.max(), x[1]);
+    assert_eq!(result.count(), 4);
+    assert!(!result.overflowed());
+
+    let too_big = [LogProb::new(-0.1_f64)?; 3];
+    let result = too_big.into_iter().log_sum_exp_detailed();
+    assert_eq!(result.value(), LogProb::certain());
+    assert_eq!(result.max(), too_big[0]);
+    assert_eq!(result.count(), 3);
+    assert!(result.overflowed());
+
+    let result = std::iter::empty::<LogProb<f64>>().log_sum_exp_detailed();
+    assert_eq!(result.value(), LogProb::impossible());
+    assert_eq!(result.max(), LogProb::impossible());
+    assert_eq!(result.count(), 0);
+    assert!(!result.overflowed());
+
+    let result = [LogProb::<f64>::impossible(); 2]
+        .iter()
+        .log_sum_exp_detailed();
+    assert_eq!(result.value(), LogProb::impossible());
+    assert_eq!(result.count(), 2);
+    Ok(())
+}
+
+#[test]
+fn weighted_quantile_values() -> Result<()> {
+    let values = [5.0, 1.0, 4.0, 2.0, 3.0, 6.0];
+    let weights = [0.1, 0.1, 0.3, 0.2, 0.3, 0.0].map(|x| LogProb::from_raw_prob(x).unwrap());
+    // Sorted: 1 (0.1), 2 (0.2), 3 (0.3), 4 (0.3), 5 (0.1), with cumulative masses
+    // 0.1, 0.3, 0.6, 0.9, 1.0.
+    assert_eq!(
+        weighted_quantiles(&values, &weights, &[0.25, 0.5, 0.75])?,
+        vec![2.0, 3.0, 4.0]
+    );
+    assert_eq!(
+        weighted_quantiles(&values, &weights, &[0.95, 0.0, 1.0, 0.05])?,
+        vec![5.0, 1.0, 5.0, 1.0]
+    );
+    assert_eq!(
+        weighted_quantiles(&values, &weights, &[0.5])?,
+        vec![weighted_median(&values, &weights)?]
+    );
+    assert!(weighted_quantiles(&values, &weights, &[])?.is_empty());
+    assert_eq!(
+        weighted_quantiles(&values[..2], &weights, &[0.5]),
+        Err(LengthMismatch)
+    );
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "quantiles must be in [0, 1]")]
+fn weighted_quantile_out_of_range() {
+    let weights = [LogProb::from_raw_prob(0.5_f64).unwrap(); 2];
+    let _ = weighted_quantiles(&[1, 2], &weights, &[1.5]);
+}